@@ -1,4 +1,3 @@
-```rust
 use anchor_lang::prelude::*;
 
 #[error_code]
@@ -549,4 +548,27 @@ pub enum StreamFlowError {
     #[msg("Authentication failed")]
     AuthenticationFailed,
     
-    #[msg("Authorization
\ No newline at end of file
+    #[msg("Authorization failed")]
+    AuthorizationFailed,
+    
+    #[msg("Stream ID too long")]
+    StreamIdTooLong,
+    
+    #[msg("Stream cannot be paused")]
+    CannotPauseStream,
+    
+    #[msg("Invalid rate per second")]
+    InvalidRatePerSecond,
+    
+    #[msg("Milestone schedule not found")]
+    MilestoneScheduleNotFound,
+    
+    #[msg("Invalid fee config")]
+    InvalidFeeConfig,
+    
+    #[msg("Automatic withdrawal not enabled")]
+    AutomaticWithdrawalDisabled,
+    
+    #[msg("Position NFT account not provided")]
+    PositionNftAccountMissing,
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ApproveMilestone<'info> {
+    #[account(
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.status == StreamStatus::Active @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+        constraint = milestone_schedule.approver == approver.key() @ StreamFlowError::InvalidMilestoneProof,
+    )]
+    pub milestone_schedule: Account<'info, MilestoneSchedule>,
+
+    pub approver: Signer<'info>,
+}
+
+pub fn approve_milestone(ctx: Context<ApproveMilestone>, index: u8) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let milestone_schedule = &mut ctx.accounts.milestone_schedule;
+
+    let amount = milestone_schedule.approve(index, current_time)?;
+
+    emit!(MilestoneApproved {
+        stream: ctx.accounts.stream.key(),
+        approver: ctx.accounts.approver.key(),
+        index,
+        amount,
+        approved_at: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MilestoneApproved {
+    pub stream: Pubkey,
+    pub approver: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+    pub approved_at: i64,
+}
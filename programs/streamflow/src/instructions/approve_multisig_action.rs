@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ApproveMultisigAction<'info> {
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig_proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = multisig,
+        constraint = !proposal.executed @ StreamFlowError::InvalidAccountState,
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    pub signer: Signer<'info>,
+}
+
+pub fn approve_multisig_action(ctx: Context<ApproveMultisigAction>) -> Result<()> {
+    let signer_index = ctx.accounts.multisig.signer_index(&ctx.accounts.signer.key())?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    require!(!proposal.approvals[signer_index], StreamFlowError::AlreadyVoted);
+    proposal.approvals[signer_index] = true;
+
+    emit!(MultisigActionApproved {
+        multisig: proposal.multisig,
+        proposal: proposal.key(),
+        signer: ctx.accounts.signer.key(),
+        approval_count: proposal.approval_count() as u8,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MultisigActionApproved {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    pub approval_count: u8,
+}
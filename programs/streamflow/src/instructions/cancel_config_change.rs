@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"config_proposal".as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = proposal.executed_at == 0 @ StreamFlowError::TimelockAlreadyExecuted,
+        constraint = proposal.cancelled_at == 0 @ StreamFlowError::InvalidTimelockProposal,
+        // Token holders voted these through, the admin cannot veto them
+        constraint = proposal.proposer != Governance::address() @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.cancelled_at = Clock::get()?.unix_timestamp;
+
+    emit!(ConfigChangeCancelled {
+        proposal: proposal.key(),
+        index: proposal.index,
+        cancelled_by: ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub cancelled_by: Pubkey,
+}
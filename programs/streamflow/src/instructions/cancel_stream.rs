@@ -1,125 +1,263 @@
-```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::state::*;
-use crate::errors::*;
+use crate::error::*;
+use crate::calculate_available_amount;
+use crate::metadata::stream_recipient;
+use crate::token::{amount_after_transfer_fee, transfer_tokens, unwrap_sol};
 
 #[derive(Accounts)]
 pub struct CancelStream<'info> {
     #[account(
         mut,
-        seeds = [b"stream", stream.sender.as_ref(), stream.recipient.as_ref(), &stream.seed.to_le_bytes()],
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
         bump = stream.bump,
-        has_one = sender @ StreamError::Unauthorized,
-        constraint = stream.status == StreamStatus::Active @ StreamError::StreamNotActive,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = matches!(stream.status, StreamStatus::Active | StreamStatus::Paused) @ StreamFlowError::StreamNotActive,
     )]
     pub stream: Account<'info, Stream>,
 
     #[account(
         mut,
-        constraint = sender_token_account.mint == stream.mint @ StreamError::InvalidMint,
-        constraint = sender_token_account.owner == sender.key() @ StreamError::InvalidTokenAccount,
+        constraint = sender_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == stream.sender @ StreamFlowError::InvalidTokenAccount,
     )]
-    pub sender_token_account: Account<'info, TokenAccount>,
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = recipient_token_account.mint == stream.mint @ StreamError::InvalidMint,
-        constraint = recipient_token_account.owner == stream.recipient @ StreamError::InvalidTokenAccount,
+        constraint = recipient_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         seeds = [b"escrow", stream.key().as_ref()],
         bump = stream.escrow_bump,
-        constraint = escrow_token_account.mint == stream.mint @ StreamError::InvalidMint,
+        constraint = escrow_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = stream.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub sender: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// CHECK: Receives native SOL when `sender_token_account` is omitted and the sender is not the authority
+    #[account(mut, address = stream.sender @ StreamFlowError::InvalidSender)]
+    pub sender: Option<AccountInfo<'info>>,
+
+    /// CHECK: Receives native SOL when `recipient_token_account` is omitted and the recipient is not the authority,
+    /// checked against the stream's holder in the handler
+    #[account(mut)]
+    pub recipient: Option<AccountInfo<'info>>,
+
+    /// The recipient's account holding the position NFT, required for streams created with a position NFT
+    pub recipient_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The stream's `WithdrawSettings`, which may not exist, read by `WithdrawSettings::load`
+    #[account(
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump,
+    )]
+    pub withdraw_settings: AccountInfo<'info>,
+
+    /// CHECK: Temporary wrapped SOL account, created and closed within the instruction
+    #[account(
+        mut,
+        seeds = [b"unwrap", stream.key().as_ref()],
+        bump,
+    )]
+    pub unwrap_account: Option<AccountInfo<'info>>,
+
+    /// Required for `StreamType::Schedule` streams
+    #[account(
+        seeds = [b"vesting_schedule", stream.key().as_ref()],
+        bump = vesting_schedule.bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
 
-    pub token_program: Program<'info, Token>,
+    /// Required for `StreamType::Milestone` streams
+    #[account(
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+    )]
+    pub milestone_schedule: Option<Account<'info, MilestoneSchedule>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.allows(Config::PAUSE_EXEMPT_CANCEL) @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", stream.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+pub fn cancel_stream<'info>(ctx: Context<'_, '_, '_, 'info, CancelStream<'info>>) -> Result<()> {
     let stream = &mut ctx.accounts.stream;
     let clock = Clock::get()?;
     let current_time = clock.unix_timestamp;
 
-    // Calculate how much has been streamed so far
-    let elapsed_time = if current_time > stream.start_time {
-        std::cmp::min(current_time - stream.start_time, stream.duration as i64)
-    } else {
-        0
-    };
+    // Whoever holds the position NFT stands in for `stream.recipient`
+    let recipient = stream_recipient(&stream.key(), stream, ctx.accounts.recipient_nft_account.as_ref())?;
+    let settings = WithdrawSettings::load(&ctx.accounts.withdraw_settings, &recipient)?;
+    match ctx.accounts.recipient_token_account.as_ref() {
+        Some(recipient_token_account) => {
+            WithdrawSettings::check_payout_account(settings.as_ref(), recipient_token_account, &recipient)?
+        },
+        None => require!(
+            settings.and_then(|settings| settings.payout_destination).is_none(),
+            StreamFlowError::InvalidTokenAccount
+        ),
+    }
+    if let Some(recipient_wallet) = ctx.accounts.recipient.as_ref() {
+        require_keys_eq!(recipient_wallet.key(), recipient, StreamFlowError::InvalidRecipient);
+    }
 
-    let streamed_amount = if elapsed_time > 0 {
-        (stream.amount as u128 * elapsed_time as u128 / stream.duration as u128) as u64
-    } else {
-        0
-    };
+    let is_sender = ctx.accounts.authority.key() == stream.sender;
+    let is_recipient = ctx.accounts.authority.key() == recipient;
 
-    let remaining_amount = stream.amount - streamed_amount;
+    require!(
+        (is_sender && stream.cancelable_by_sender) ||
+        (is_recipient && stream.cancelable_by_recipient),
+        StreamFlowError::CancellationNotAllowed
+    );
 
-    // Transfer streamed amount to recipient if any
-    if streamed_amount > 0 {
-        let stream_key = stream.key();
-        let seeds = &[
-            b"escrow",
-            stream_key.as_ref(),
-            &[stream.escrow_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.escrow_token_account.to_account_info(),
-        };
-
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-        token::transfer(cpi_ctx, streamed_amount)?;
+    // Everything vested but not yet withdrawn goes to the recipient,
+    // the unvested remainder goes back to the sender
+    let streamed_amount = calculate_available_amount(
+        stream,
+        ctx.accounts.vesting_schedule.as_deref(),
+        ctx.accounts.milestone_schedule.as_deref(),
+        current_time,
+    )?;
+    let remaining_amount = stream
+        .remaining_amount()
+        .checked_sub(streamed_amount)
+        .ok_or(StreamFlowError::MathematicalUnderflow)?;
+
+    // The cancellation fee comes out of the refund, never out of vested tokens
+    let fee_amount = FeeConfig::calculate_fee(remaining_amount, ctx.accounts.config.fee_config.cancellation_fee_rate)?;
+    let returned_amount = remaining_amount
+        .checked_sub(fee_amount)
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+
+    let seeds = &[
+        b"stream",
+        stream.sender.as_ref(),
+        stream.stream_id.as_bytes(),
+        &[stream.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    // Streamed amount goes to the recipient, the remainder back to the sender.
+    // A party without a token account is paid in native SOL instead.
+    let payouts = [
+        (
+            ctx.accounts.recipient_token_account.as_ref(),
+            ctx.accounts.recipient.as_ref(),
+            is_recipient,
+            streamed_amount,
+        ),
+        (
+            ctx.accounts.sender_token_account.as_ref(),
+            ctx.accounts.sender.as_ref(),
+            is_sender,
+            returned_amount,
+        ),
+    ];
+    let mut native_deliveries = Vec::new();
+    for (token_account, wallet, is_authority, payout_amount) in payouts {
+        if payout_amount == 0 {
+            continue;
+        }
+
+        match token_account {
+            Some(token_account) => transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                token_account.to_account_info(),
+                stream.to_account_info(),
+                ctx.remaining_accounts,
+                payout_amount,
+                signer_seeds,
+            )?,
+            None => {
+                let wallet = if is_authority {
+                    ctx.accounts.authority.to_account_info()
+                } else {
+                    wallet.ok_or(StreamFlowError::InvalidTokenAccount)?.to_account_info()
+                };
+                native_deliveries.push((wallet, payout_amount));
+            },
+        }
     }
 
-    // Return remaining amount to sender
-    if remaining_amount > 0 {
+    if !native_deliveries.is_empty() {
+        let unwrap_account = ctx.accounts.unwrap_account.as_ref()
+            .ok_or(StreamFlowError::InvalidTokenAccount)?;
         let stream_key = stream.key();
-        let seeds = &[
-            b"escrow",
-            stream_key.as_ref(),
-            &[stream.escrow_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.sender_token_account.to_account_info(),
-            authority: ctx.accounts.escrow_token_account.to_account_info(),
-        };
-
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-
-        token::transfer(cpi_ctx, remaining_amount)?;
+        let unwrap_seeds = &[b"unwrap", stream_key.as_ref(), &[ctx.bumps.unwrap_account]];
+
+        unwrap_sol(
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            stream.to_account_info(),
+            signer_seeds,
+            unwrap_account.to_account_info(),
+            unwrap_seeds,
+            ctx.accounts.authority.to_account_info(),
+            &native_deliveries,
+        )?;
+    }
+
+    if fee_amount > 0 {
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.fee_vault.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            fee_amount,
+            signer_seeds,
+        )?;
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, fee_amount)?)?;
     }
 
     // Update stream status
+    stream.update_after_withdrawal(streamed_amount, current_time)?;
     stream.status = StreamStatus::Cancelled;
-    stream.cancelled_at = Some(current_time);
-    stream.withdrawn_amount = streamed_amount;
+    stream.canceled_at = Some(current_time);
 
     emit!(StreamCancelled {
         stream: stream.key(),
         sender: stream.sender,
         recipient: stream.recipient,
+        cancelled_by: ctx.accounts.authority.key(),
         streamed_amount,
-        returned_amount: remaining_amount,
+        returned_amount,
+        fee_amount,
         cancelled_at: current_time,
     });
 
@@ -131,8 +269,9 @@ pub struct StreamCancelled {
     pub stream: Pubkey,
     pub sender: Pubkey,
     pub recipient: Pubkey,
+    pub cancelled_by: Pubkey,
     pub streamed_amount: u64,
     pub returned_amount: u64,
+    pub fee_amount: u64,
     pub cancelled_at: i64,
 }
-```
\ No newline at end of file
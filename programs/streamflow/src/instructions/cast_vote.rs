@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::create_pda_account;
+use crate::token::{amount_after_transfer_fee, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        seeds = [b"governance"],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"governance_proposal".as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// CHECK: Created in the handler, an existing record means the voter already voted
+    #[account(
+        mut,
+        seeds = [b"vote_record", proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ StreamFlowError::InvalidTokenAccount,
+        constraint = voter_token_account.mint == governance.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub voter_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = governance.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = governance.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn cast_vote<'info>(
+    ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+    approve: bool,
+    votes: u64,
+) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp < ctx.accounts.proposal.voting_ends_at,
+        StreamFlowError::VotingPeriodExpired
+    );
+    require!(
+        votes > 0 && votes <= ctx.accounts.voter_token_account.amount,
+        StreamFlowError::InvalidVotingPower
+    );
+    require!(ctx.accounts.vote_record.data_is_empty(), StreamFlowError::AlreadyVoted);
+
+    // Voting tokens stay locked in the vault until voting ends, so they cannot vote twice
+    transfer_tokens(
+        &ctx.accounts.token_program,
+        ctx.accounts.voter_token_account.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.voter.to_account_info(),
+        ctx.remaining_accounts,
+        votes,
+        &[],
+    )?;
+    let votes = amount_after_transfer_fee(&ctx.accounts.mint, votes)?;
+
+    let proposal_key = ctx.accounts.proposal.key();
+    let voter_key = ctx.accounts.voter.key();
+    let bump = ctx.bumps.vote_record;
+    create_pda_account(
+        &ctx.accounts.system_program,
+        ctx.accounts.voter.to_account_info(),
+        ctx.accounts.vote_record.clone(),
+        VoteRecord::LEN,
+        ctx.program_id,
+        &[b"vote_record", proposal_key.as_ref(), voter_key.as_ref(), &[bump]],
+    )?;
+    VoteRecord {
+        proposal: proposal_key,
+        voter: voter_key,
+        votes,
+        approve,
+        bump,
+    }
+    .try_serialize(&mut &mut ctx.accounts.vote_record.try_borrow_mut_data()?[..])?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.record_vote(approve, votes)?;
+
+    emit!(VoteCast {
+        proposal: proposal_key,
+        voter: voter_key,
+        approve,
+        votes,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub votes: u64,
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::close_pda_account;
+use crate::token::{harvest_withheld_fees, is_native_mint, transfer_tokens};
+
+/// Permissionless: the rent of every closed account goes back to the sender, who paid it,
+/// except for `withdraw_settings`, whose rent goes back to the holder that created it.
+///
+/// Every account derived from the stream address is closed as well, so a new
+/// stream reusing the same `stream_id` starts from a clean slate.
+#[derive(Accounts)]
+pub struct CloseStream<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.status == StreamStatus::Cancelled || stream.is_fully_withdrawn() @ StreamFlowError::AccountStateTransitionInvalid,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", stream.key().as_ref()],
+        bump = stream.escrow_bump,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives anything left in the escrow, required when it is not empty
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == stream.sender @ StreamFlowError::InvalidTokenAccount,
+        constraint = sender_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Writable so transfer fees withheld in the escrow can be harvested to it
+    #[account(
+        mut,
+        address = stream.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Receives the rent, checked against the stream
+    #[account(mut, address = stream.sender @ StreamFlowError::InvalidSender)]
+    pub sender: AccountInfo<'info>,
+
+    /// CHECK: Closed if it exists. Required for `StreamType::Schedule` streams
+    #[account(
+        mut,
+        seeds = [b"vesting_schedule", stream.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<AccountInfo<'info>>,
+
+    /// CHECK: Closed if it exists. Required for `StreamType::Milestone` streams
+    #[account(
+        mut,
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump,
+    )]
+    pub milestone_schedule: Option<AccountInfo<'info>>,
+
+    /// CHECK: Closed if it exists. Required for `StreamType::Milestone` streams
+    #[account(
+        mut,
+        seeds = [b"price_condition", stream.key().as_ref()],
+        bump,
+    )]
+    pub price_condition: Option<AccountInfo<'info>>,
+
+    /// CHECK: The stream's `WithdrawSettings`, closed if it exists
+    #[account(
+        mut,
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump,
+    )]
+    pub withdraw_settings: AccountInfo<'info>,
+
+    /// CHECK: Receives the rent of `withdraw_settings`, required when it exists and checked against it
+    #[account(mut)]
+    pub withdraw_settings_payer: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn close_stream<'info>(ctx: Context<'_, '_, '_, 'info, CloseStream<'info>>) -> Result<()> {
+    let stream = &ctx.accounts.stream;
+
+    let schedule_accounts = match stream.stream_type {
+        StreamType::Schedule => vec![&ctx.accounts.vesting_schedule],
+        StreamType::Milestone => vec![&ctx.accounts.milestone_schedule, &ctx.accounts.price_condition],
+        _ => vec![],
+    };
+    for schedule_account in schedule_accounts {
+        let schedule_account = schedule_account.as_ref().ok_or(StreamFlowError::InvalidAccountState)?;
+        if *schedule_account.owner == crate::ID {
+            close_pda_account(schedule_account, &ctx.accounts.sender)?;
+        }
+    }
+
+    // The settings hold no funds, only who may withdraw and where payouts go
+    let withdraw_settings = &ctx.accounts.withdraw_settings;
+    if *withdraw_settings.owner == crate::ID {
+        let settings = WithdrawSettings::try_deserialize(&mut &withdraw_settings.try_borrow_data()?[..])?;
+        let payer = ctx.accounts.withdraw_settings_payer.as_ref()
+            .ok_or(StreamFlowError::InvalidAccountState)?;
+        require_keys_eq!(payer.key(), settings.payer, StreamFlowError::InvalidAccountState);
+        close_pda_account(withdraw_settings, payer)?;
+    }
+
+    let seeds = &[
+        b"stream",
+        stream.sender.as_ref(),
+        stream.stream_id.as_bytes(),
+        &[stream.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    // Anyone can send tokens to the escrow, so leftovers are swept instead of blocking the close.
+    // Closing a wrapped SOL account releases its balance along with the rent.
+    let leftover_amount = ctx.accounts.escrow_token_account.amount;
+    if leftover_amount > 0 && !is_native_mint(&stream.mint) {
+        let sender_token_account = ctx.accounts.sender_token_account.as_ref()
+            .ok_or(StreamFlowError::InvalidTokenAccount)?;
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            sender_token_account.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            leftover_amount,
+            signer_seeds,
+        )?;
+    }
+
+    harvest_withheld_fees(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        ctx.accounts.escrow_token_account.to_account_info(),
+    )?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow_token_account.to_account_info(),
+            destination: ctx.accounts.sender.to_account_info(),
+            authority: stream.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    emit!(StreamClosed {
+        stream: stream.key(),
+        sender: stream.sender,
+        status: stream.status,
+        leftover_amount,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamClosed {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub status: StreamStatus,
+    pub leftover_amount: u64,
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::calculate_available_amount;
+use crate::metadata::stream_recipient;
+use crate::token::{amount_after_transfer_fee, transfer_tokens};
+
+/// Permissionless: any keeper can push everything vested to the recipient's associated token
+/// account, or to its payout destination when one is registered
+#[derive(Accounts)]
+pub struct CrankWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.automatic_withdrawal @ StreamFlowError::AutomaticWithdrawalDisabled,
+        constraint = matches!(stream.status, StreamStatus::Active | StreamStatus::Paused) @ StreamFlowError::StreamNotActive,
+        constraint = stream.withdrawn_amount < stream.amount @ StreamFlowError::StreamFullyWithdrawn
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        constraint = stream_token_account.key() == stream.escrow_token_account @ StreamFlowError::InvalidEscrowAccount,
+        constraint = stream_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = stream_token_account.owner == stream.key() @ StreamFlowError::InvalidTokenAccount
+    )]
+    pub stream_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The recipient's associated token account, or the registered payout destination when there is one
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The recipient's account holding the position NFT, required for streams created with a position NFT
+    pub recipient_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The stream's `WithdrawSettings`, which may not exist, read by `WithdrawSettings::load`
+    #[account(
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump,
+    )]
+    pub withdraw_settings: AccountInfo<'info>,
+
+    #[account(address = stream.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub cranker: Signer<'info>,
+
+    /// Receives the crank tip, no tip is paid when omitted
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = cranker_token_account.owner == cranker.key() @ StreamFlowError::InvalidTokenAccount
+    )]
+    pub cranker_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for `StreamType::Schedule` streams
+    #[account(
+        seeds = [b"vesting_schedule", stream.key().as_ref()],
+        bump = vesting_schedule.bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+
+    /// Required for `StreamType::Milestone` streams
+    #[account(
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+    )]
+    pub milestone_schedule: Option<Account<'info, MilestoneSchedule>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.allows(Config::PAUSE_EXEMPT_WITHDRAW) @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", stream.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn crank_withdraw<'info>(ctx: Context<'_, '_, '_, 'info, CrankWithdraw<'info>>) -> Result<()> {
+    let stream = &mut ctx.accounts.stream;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(current_time >= stream.start_time, StreamFlowError::StreamNotStarted);
+
+    // Streams with a position NFT pay whoever holds it now
+    let recipient = stream_recipient(&stream.key(), stream, ctx.accounts.recipient_nft_account.as_ref())?;
+    let settings = WithdrawSettings::load(&ctx.accounts.withdraw_settings, &recipient)?;
+    WithdrawSettings::check_payout_account(
+        settings.as_ref(),
+        &ctx.accounts.recipient_token_account,
+        &recipient,
+    )?;
+    // The keeper picks the account, so without a registered destination only the ATA is accepted
+    if settings.as_ref().and_then(|settings| settings.payout_destination).is_none() {
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.key(),
+            get_associated_token_address_with_program_id(&recipient, &stream.mint, ctx.accounts.token_program.key),
+            StreamFlowError::InvalidTokenAccount
+        );
+    }
+
+    let withdraw_amount = calculate_available_amount(
+        stream,
+        ctx.accounts.vesting_schedule.as_deref(),
+        ctx.accounts.milestone_schedule.as_deref(),
+        current_time,
+    )?;
+    require!(withdraw_amount > 0, StreamFlowError::InsufficientWithdrawableAmount);
+
+    // The protocol fee and the keeper's tip both come out of the withdrawn amount
+    let config = &ctx.accounts.config;
+    let fee_amount = FeeConfig::calculate_fee(withdraw_amount, config.fee_config.withdrawal_fee_rate)?;
+    let tip_amount = match ctx.accounts.cranker_token_account {
+        Some(_) => FeeConfig::calculate_fee(withdraw_amount, config.crank_tip_rate)?,
+        None => 0,
+    };
+    let recipient_amount = withdraw_amount
+        .checked_sub(fee_amount)
+        .and_then(|result| result.checked_sub(tip_amount))
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+
+    stream.update_after_withdrawal(withdraw_amount, current_time)?;
+
+    if stream.is_fully_withdrawn() {
+        stream.status = StreamStatus::Completed;
+    }
+
+    let seeds = &[
+        b"stream",
+        stream.sender.as_ref(),
+        stream.stream_id.as_bytes(),
+        &[stream.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    transfer_tokens(
+        &ctx.accounts.token_program,
+        ctx.accounts.stream_token_account.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.recipient_token_account.to_account_info(),
+        stream.to_account_info(),
+        ctx.remaining_accounts,
+        recipient_amount,
+        signer_seeds,
+    )?;
+
+    if let Some(cranker_token_account) = ctx.accounts.cranker_token_account.as_ref() {
+        if tip_amount > 0 {
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                ctx.accounts.stream_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                cranker_token_account.to_account_info(),
+                stream.to_account_info(),
+                ctx.remaining_accounts,
+                tip_amount,
+                signer_seeds,
+            )?;
+        }
+    }
+
+    if fee_amount > 0 {
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.stream_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.fee_vault.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            fee_amount,
+            signer_seeds,
+        )?;
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, fee_amount)?)?;
+    }
+
+    emit!(CrankWithdrawEvent {
+        stream: stream.key(),
+        recipient,
+        cranker: ctx.accounts.cranker.key(),
+        amount: withdraw_amount,
+        fee_amount,
+        tip_amount,
+        timestamp: current_time,
+        remaining_amount: stream.remaining_amount(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct CrankWithdrawEvent {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub fee_amount: u64,
+    pub tip_amount: u64,
+    pub timestamp: i64,
+    pub remaining_amount: u64,
+}
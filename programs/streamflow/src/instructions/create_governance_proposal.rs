@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CreateGovernanceProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance"],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = GovernanceProposal::LEN,
+        seeds = [b"governance_proposal".as_ref(), &governance.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// Only token holders can put changes to a vote
+    #[account(
+        constraint = proposer_token_account.owner == proposer.key() @ StreamFlowError::InvalidTokenAccount,
+        constraint = proposer_token_account.mint == governance.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = proposer_token_account.amount > 0 @ StreamFlowError::InvalidVotingPower,
+    )]
+    pub proposer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_governance_proposal(
+    ctx: Context<CreateGovernanceProposal>,
+    change: ConfigChange,
+) -> Result<()> {
+    change.validate()?;
+
+    let governance = &mut ctx.accounts.governance;
+    let voting_ends_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(governance.voting_period)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.index = governance.proposal_count;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.change = change;
+    proposal.voting_ends_at = voting_ends_at;
+    proposal.yes_votes = 0;
+    proposal.no_votes = 0;
+    proposal.queued = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    governance.proposal_count = governance.proposal_count
+        .checked_add(1)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    emit!(GovernanceProposalCreated {
+        proposal: proposal.key(),
+        index: proposal.index,
+        proposer: proposal.proposer,
+        change: proposal.change.clone(),
+        voting_ends_at,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GovernanceProposalCreated {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub change: ConfigChange,
+    pub voting_ends_at: i64,
+}
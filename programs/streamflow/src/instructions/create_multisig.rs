@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Multisig::LEN,
+        seeds = [b"multisig", create_key.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: Signer PDA that acts as stream sender, holds no data
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump,
+    )]
+    pub multisig_signer: AccountInfo<'info>,
+
+    /// One-off key, signing stops anyone else from claiming the address
+    pub create_key: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_multisig(
+    ctx: Context<CreateMultisig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    Multisig::validate_signers(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.create_key = ctx.accounts.create_key.key();
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.proposal_count = 0;
+    multisig.bump = ctx.bumps.multisig;
+    multisig.signer_bump = ctx.bumps.multisig_signer;
+
+    emit!(MultisigCreated {
+        multisig: multisig.key(),
+        multisig_signer: ctx.accounts.multisig_signer.key(),
+        signers: multisig.signers.clone(),
+        threshold,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub multisig: Pubkey,
+    pub multisig_signer: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
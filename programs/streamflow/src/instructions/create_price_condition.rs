@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CreatePriceCondition<'info> {
+    #[account(
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.sender == sender.key() @ StreamFlowError::InvalidSender,
+        constraint = stream.stream_type == StreamType::Milestone @ StreamFlowError::InvalidStreamType,
+        constraint = stream.status == StreamStatus::Active @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+    )]
+    pub milestone_schedule: Account<'info, MilestoneSchedule>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = PriceCondition::LEN,
+        seeds = [b"price_condition", stream.key().as_ref()],
+        bump
+    )]
+    pub price_condition: Account<'info, PriceCondition>,
+
+    /// CHECK: Pyth price account or `PriceFeed`, parsed when a milestone is unlocked
+    pub price_feed: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_price_condition(
+    ctx: Context<CreatePriceCondition>,
+    expo: i32,
+    max_staleness: i64,
+    thresholds: Vec<i64>,
+) -> Result<()> {
+    let milestone_schedule = &ctx.accounts.milestone_schedule;
+
+    // Only the condition itself may approve, otherwise the approver could bypass the oracle
+    require!(
+        milestone_schedule.approver == ctx.accounts.price_condition.key(),
+        StreamFlowError::InvalidMilestoneProof
+    );
+    PriceCondition::validate_thresholds(&thresholds, milestone_schedule.milestones.len(), max_staleness)?;
+
+    let price_condition = &mut ctx.accounts.price_condition;
+    price_condition.stream = ctx.accounts.stream.key();
+    price_condition.price_feed = ctx.accounts.price_feed.key();
+    price_condition.expo = expo;
+    price_condition.max_staleness = max_staleness;
+    price_condition.bump = ctx.bumps.price_condition;
+    price_condition.thresholds = thresholds;
+
+    emit!(PriceConditionCreated {
+        stream: price_condition.stream,
+        price_condition: price_condition.key(),
+        price_feed: price_condition.price_feed,
+        expo,
+        max_staleness,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PriceConditionCreated {
+    pub stream: Pubkey,
+    pub price_condition: Pubkey,
+    pub price_feed: Pubkey,
+    pub expo: i32,
+    pub max_staleness: i64,
+}
@@ -1,8 +1,16 @@
-```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::{self, AssociatedToken, Create};
+use anchor_spl::token::{self, spl_token, InitializeMint2, MintTo, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
-use crate::errors::*;
+use crate::error::*;
+use crate::create_pda_account;
+use crate::metadata::{
+    create_master_edition, create_metadata_account, MAX_NAME_LEN, MAX_URI_LEN,
+    TOKEN_METADATA_PROGRAM_ID,
+};
+use crate::token::{amount_after_transfer_fee, is_native_mint, transfer_tokens, wrap_sol};
 
 #[derive(Accounts)]
 #[instruction(stream_id: String)]
@@ -21,10 +29,11 @@ pub struct CreateStream<'info> {
         payer = sender,
         token::mint = mint,
         token::authority = stream,
+        token::token_program = token_program,
         seeds = [b"escrow", stream.key().as_ref()],
         bump
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub sender: Signer<'info>,
@@ -32,98 +41,490 @@ pub struct CreateStream<'info> {
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub recipient: AccountInfo<'info>,
 
+    /// Omit to pay a wrapped SOL stream in lamports
     #[account(
         mut,
         constraint = sender_token_account.owner == sender.key(),
         constraint = sender_token_account.mint == mint.key()
     )]
-    pub sender_token_account: Account<'info, TokenAccount>,
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    pub mint: Account<'info, token::Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"mint_registry"],
+        bump = mint_registry.bump,
+    )]
+    pub mint_registry: Account<'info, MintRegistry>,
+
+    /// CHECK: Only the address is constrained, an empty account means the mint is not listed
+    #[account(
+        seeds = [b"mint_entry", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_entry: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Created here for `StreamType::Schedule` streams, required for them only
+    #[account(
+        mut,
+        seeds = [b"vesting_schedule", stream.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<AccountInfo<'info>>,
+
+    /// CHECK: Created here for `StreamType::Milestone` streams, required for them only
+    #[account(
+        mut,
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump,
+    )]
+    pub milestone_schedule: Option<AccountInfo<'info>>,
+
+    /// CHECK: Mint of the position NFT, created here when one is requested and checked against `stream_nft_mint`
+    #[account(mut)]
+    pub nft_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: The recipient's associated token account for `nft_mint`, created by the associated token program
+    #[account(mut)]
+    pub recipient_nft_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: Created and checked by the token metadata program
+    #[account(mut)]
+    pub metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Created and checked by the token metadata program
+    #[account(mut)]
+    pub master_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: Checked against the known program id
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    /// The position NFT is a classic SPL token whatever program the streamed mint uses
+    pub nft_token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handler(
-    ctx: Context<CreateStream>,
-    stream_id: String,
+/// The accounts a new stream is tied to
+pub(crate) struct StreamAccounts {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+/// Validate `params` and build the stream they describe.
+///
+/// `amount` is what actually reaches the escrow, which is less than
+/// `params.amount` for transfer-fee mints. `schedule` is required for
+/// `StreamType::Schedule` and `StreamType::Milestone` streams and refused
+/// for every other type.
+pub(crate) fn build_stream(
+    params: StreamParams,
     amount: u64,
-    start_time: i64,
-    end_time: i64,
-    cliff_time: Option<i64>,
-    cancelable_by_sender: bool,
-    cancelable_by_recipient: bool,
-    transferable_by_sender: bool,
-    transferable_by_recipient: bool,
-) -> Result<()> {
-    let current_time = Clock::get()?.unix_timestamp;
+    accounts: StreamAccounts,
+    schedule: Option<&StreamSchedule>,
+    current_time: i64,
+) -> Result<Stream> {
+    let StreamParams {
+        stream_id,
+        start_time,
+        end_time,
+        cliff_time,
+        stream_type,
+        release_frequency,
+        rate_per_second,
+        initial_unlock,
+        cliff_unlock,
+        ..
+    } = params;
+
+    // Rate streams run until the deposit is exhausted, so their end is derived
+    let end_time = if stream_type == StreamType::Rate {
+        Stream::runway_end(start_time, amount, rate_per_second)?
+    } else {
+        require!(rate_per_second == 0, StreamFlowError::InvalidRatePerSecond);
+        end_time
+    };
 
     // Validation checks
-    require!(amount > 0, StreamFlowError::InvalidAmount);
-    require!(start_time >= current_time, StreamFlowError::InvalidStartTime);
-    require!(end_time > start_time, StreamFlowError::InvalidEndTime);
-    require!(stream_id.len() <= 32, StreamFlowError::StreamIdTooLong);
+    Stream::validate_params(start_time, end_time, cliff_time, amount)?;
+    require!(start_time >= current_time, StreamFlowError::StartTimeInPast);
+    require!(stream_id.len() <= MAX_STREAM_ID_LEN, StreamFlowError::StreamIdTooLong);
 
-    if let Some(cliff) = cliff_time {
-        require!(cliff >= start_time && cliff <= end_time, StreamFlowError::InvalidCliffTime);
+    if stream_type == StreamType::Cliff {
+        require!(cliff_time.is_some(), StreamFlowError::InvalidStreamType);
     }
 
-    // Check sender has sufficient balance
+    // Withdrawals and cancellation need the curve or tranches, so they are set up with the stream
+    match (stream_type, schedule) {
+        (StreamType::Schedule, Some(StreamSchedule::Checkpoints { checkpoints, .. })) => {
+            VestingSchedule::validate_checkpoints(checkpoints, start_time, end_time, amount)?
+        },
+        (StreamType::Milestone, Some(StreamSchedule::Milestones { amounts, .. })) => {
+            MilestoneSchedule::validate_amounts(amounts, amount)?
+        },
+        (StreamType::Schedule, None) => return err!(StreamFlowError::VestingScheduleNotFound),
+        (StreamType::Milestone, None) => return err!(StreamFlowError::MilestoneScheduleNotFound),
+        (_, Some(_)) => return err!(StreamFlowError::InvalidStreamType),
+        (_, None) => {},
+    }
+
+    // Periodic unlocks only apply to linear streams and need at least one full period
+    if release_frequency != WithdrawFrequency::Continuous {
+        let first_unlock = release_frequency
+            .period_boundary(start_time, 1)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        require!(
+            stream_type == StreamType::Linear && first_unlock <= end_time,
+            StreamFlowError::InvalidLinearReleaseParameters
+        );
+    }
+
+    // Up-front unlocks come out of `amount`, the rest follows the stream type
+    let initial_unlock_amount = initial_unlock.resolve(amount)?;
+    let cliff_unlock_amount = cliff_unlock.resolve(amount)?;
+    let total_unlock_amount = initial_unlock_amount
+        .checked_add(cliff_unlock_amount)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+    require!(total_unlock_amount <= amount, StreamFlowError::InvalidUnlockPercentage);
     require!(
-        ctx.accounts.sender_token_account.amount >= amount,
-        StreamFlowError::InsufficientBalance
+        cliff_unlock_amount == 0 || cliff_time.is_some(),
+        StreamFlowError::InvalidUnlockPercentage
+    );
+    require!(
+        total_unlock_amount == 0 || !matches!(stream_type, StreamType::Rate | StreamType::Schedule | StreamType::Milestone),
+        StreamFlowError::InvalidStreamType
     );
 
-    let stream = &mut ctx.accounts.stream;
-    let bump = ctx.bumps.stream;
-
-    // Initialize stream account
-    stream.sender = ctx.accounts.sender.key();
-    stream.recipient = ctx.accounts.recipient.key();
-    stream.mint = ctx.accounts.mint.key();
-    stream.escrow_token_account = ctx.accounts.escrow_token_account.key();
-    stream.amount = amount;
-    stream.withdrawn_amount = 0;
-    stream.start_time = start_time;
-    stream.end_time = end_time;
-    stream.cliff_time = cliff_time;
-    stream.created_at = current_time;
-    stream.canceled_at = None;
-    stream.cancelable_by_sender = cancelable_by_sender;
-    stream.cancelable_by_recipient = cancelable_by_recipient;
-    stream.transferable_by_sender = transferable_by_sender;
-    stream.transferable_by_recipient = transferable_by_recipient;
-    stream.stream_id = stream_id;
-    stream.bump = bump;
-    stream.escrow_bump = ctx.bumps.escrow_token_account;
-
-    // Transfer tokens from sender to escrow
-    let transfer_instruction = Transfer {
-        from: ctx.accounts.sender_token_account.to_account_info(),
-        to: ctx.accounts.escrow_token_account.to_account_info(),
-        authority: ctx.accounts.sender.to_account_info(),
+    Ok(Stream {
+        version: STREAM_VERSION,
+        bump: accounts.bump,
+        escrow_bump: accounts.escrow_bump,
+        sender: accounts.sender,
+        recipient: accounts.recipient,
+        mint: accounts.mint,
+        escrow_token_account: accounts.escrow_token_account,
+        amount,
+        withdrawn_amount: 0,
+        start_time,
+        end_time,
+        created_at: current_time,
+        last_withdrawn_at: 0,
+        stream_type,
+        release_frequency,
+        status: StreamStatus::Active,
+        cancelable_by_sender: params.cancelable_by_sender,
+        cancelable_by_recipient: params.cancelable_by_recipient,
+        transferable_by_sender: params.transferable_by_sender,
+        transferable_by_recipient: params.transferable_by_recipient,
+        cliff_time,
+        canceled_at: None,
+        stream_id,
+        can_pause: params.can_pause,
+        paused_at: 0,
+        metrics: StreamMetrics {
+            total_deposited: amount,
+            ..StreamMetrics::default()
+        },
+        rate_per_second,
+        initial_unlock_amount,
+        cliff_unlock_amount,
+        automatic_withdrawal: params.automatic_withdrawal,
+        nft_recipient: false,
+        withdrawal_public: params.withdrawal_public,
+        reserved: [0; 52],
+    })
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateStream<'info>>,
+    params: StreamParams,
+    schedule: Option<StreamSchedule>,
+    position_nft: Option<PositionNft>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.mint_registry.check_mint(
+        &ctx.accounts.mint,
+        MintEntry::is_listed(&ctx.accounts.mint_entry),
+    )?;
+
+    // Transfer-fee mints withhold part of the deposit, only what reaches the escrow is streamed
+    let deposit_amount = params.amount;
+    let amount = amount_after_transfer_fee(&ctx.accounts.mint, deposit_amount)?;
+
+    let new_stream = build_stream(
+        params,
+        amount,
+        StreamAccounts {
+            sender: ctx.accounts.sender.key(),
+            recipient: ctx.accounts.recipient.key(),
+            mint: ctx.accounts.mint.key(),
+            escrow_token_account: ctx.accounts.escrow_token_account.key(),
+            bump: ctx.bumps.stream,
+            escrow_bump: ctx.bumps.escrow_token_account,
+        },
+        schedule.as_ref(),
+        current_time,
+    )?;
+
+    // The platform fee is charged on top of the streamed amount
+    let fee_amount = FeeConfig::calculate_fee(amount, ctx.accounts.config.fee_config.platform_fee_rate)?;
+    let total_amount = deposit_amount
+        .checked_add(fee_amount)
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+
+    // Without a token account the deposit is paid in lamports and wrapped into the escrow
+    let sender_balance = match ctx.accounts.sender_token_account.as_ref() {
+        Some(sender_token_account) => sender_token_account.amount,
+        None => {
+            require!(is_native_mint(&ctx.accounts.mint.key()), StreamFlowError::InvalidTokenAccount);
+            ctx.accounts.sender.lamports()
+        },
     };
 
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_instruction,
+    // Check sender has sufficient balance
+    require!(
+        sender_balance >= total_amount,
+        StreamFlowError::InsufficientTokenBalance
     );
 
-    token::transfer(cpi_ctx, amount)?;
+    let stream = &mut ctx.accounts.stream;
+    stream.set_inner(new_stream);
+
+    let stream_key = stream.key();
+    match schedule {
+        Some(StreamSchedule::Checkpoints { checkpoints, interpolate }) => {
+            let vesting_schedule = ctx.accounts.vesting_schedule
+                .as_ref()
+                .ok_or(StreamFlowError::VestingScheduleNotFound)?;
+            let bump = ctx.bumps.vesting_schedule;
+
+            create_pda_account(
+                &ctx.accounts.system_program,
+                ctx.accounts.sender.to_account_info(),
+                vesting_schedule.clone(),
+                VestingSchedule::LEN,
+                ctx.program_id,
+                &[b"vesting_schedule", stream_key.as_ref(), &[bump]],
+            )?;
+            let checkpoint_count = checkpoints.len() as u8;
+            VestingSchedule {
+                stream: stream_key,
+                interpolate,
+                bump,
+                checkpoints,
+            }
+            .try_serialize(&mut &mut vesting_schedule.try_borrow_mut_data()?[..])?;
+
+            emit!(VestingScheduleCreated {
+                stream: stream_key,
+                vesting_schedule: vesting_schedule.key(),
+                checkpoint_count,
+                interpolate,
+            });
+        },
+        Some(StreamSchedule::Milestones { approver, amounts }) => {
+            let milestone_schedule = ctx.accounts.milestone_schedule
+                .as_ref()
+                .ok_or(StreamFlowError::MilestoneScheduleNotFound)?;
+            let bump = ctx.bumps.milestone_schedule;
+
+            create_pda_account(
+                &ctx.accounts.system_program,
+                ctx.accounts.sender.to_account_info(),
+                milestone_schedule.clone(),
+                MilestoneSchedule::LEN,
+                ctx.program_id,
+                &[b"milestone_schedule", stream_key.as_ref(), &[bump]],
+            )?;
+            let milestone_count = amounts.len() as u8;
+            MilestoneSchedule {
+                stream: stream_key,
+                approver,
+                bump,
+                milestones: amounts
+                    .into_iter()
+                    .map(|amount| Milestone { amount, approved_at: 0 })
+                    .collect(),
+            }
+            .try_serialize(&mut &mut milestone_schedule.try_borrow_mut_data()?[..])?;
+
+            emit!(MilestoneScheduleCreated {
+                stream: stream_key,
+                milestone_schedule: milestone_schedule.key(),
+                approver,
+                milestone_count,
+            });
+        },
+        None => {},
+    }
+
+    // From here on whoever holds the NFT is the one entitled to withdraw
+    if let Some(PositionNft { name, uri }) = position_nft {
+        require!(name.len() <= MAX_NAME_LEN, StreamFlowError::StreamNameTooLong);
+        require!(uri.len() <= MAX_URI_LEN, StreamFlowError::StreamMetadataTooLarge);
+
+        let missing = || error!(StreamFlowError::PositionNftAccountMissing);
+        let nft_mint = ctx.accounts.nft_mint.as_ref().ok_or_else(missing)?;
+        let recipient_nft_account = ctx.accounts.recipient_nft_account.as_ref().ok_or_else(missing)?;
+        let metadata = ctx.accounts.metadata.as_ref().ok_or_else(missing)?;
+        let master_edition = ctx.accounts.master_edition.as_ref().ok_or_else(missing)?;
+        let token_metadata_program = ctx.accounts.token_metadata_program.as_ref().ok_or_else(missing)?;
+        let nft_token_program = ctx.accounts.nft_token_program.as_ref().ok_or_else(missing)?;
+        let associated_token_program = ctx.accounts.associated_token_program.as_ref().ok_or_else(missing)?;
+
+        let (expected_mint, nft_mint_bump) = Pubkey::find_program_address(
+            &[b"stream_nft", stream_key.as_ref(), &stream.created_at.to_le_bytes()],
+            ctx.program_id,
+        );
+        require_keys_eq!(nft_mint.key(), expected_mint, StreamFlowError::InvalidAccountState);
+
+        create_pda_account(
+            &ctx.accounts.system_program,
+            ctx.accounts.sender.to_account_info(),
+            nft_mint.clone(),
+            spl_token::state::Mint::LEN,
+            &token::ID,
+            &[b"stream_nft", stream_key.as_ref(), &stream.created_at.to_le_bytes(), &[nft_mint_bump]],
+        )?;
+        token::initialize_mint2(
+            CpiContext::new(nft_token_program.to_account_info(), InitializeMint2 { mint: nft_mint.clone() }),
+            0,
+            &stream_key,
+            Some(&stream_key),
+        )?;
+
+        associated_token::create(CpiContext::new(
+            associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.sender.to_account_info(),
+                associated_token: recipient_nft_account.clone(),
+                authority: ctx.accounts.recipient.to_account_info(),
+                mint: nft_mint.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: nft_token_program.to_account_info(),
+            },
+        ))?;
+
+        let seeds = &[
+            b"stream",
+            stream.sender.as_ref(),
+            stream.stream_id.as_bytes(),
+            &[stream.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                nft_token_program.to_account_info(),
+                MintTo {
+                    mint: nft_mint.clone(),
+                    to: recipient_nft_account.clone(),
+                    authority: stream.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+
+        create_metadata_account(
+            token_metadata_program.clone(),
+            metadata.clone(),
+            nft_mint.clone(),
+            stream.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+            name,
+            uri,
+            signer_seeds,
+        )?;
 
-    emit!(StreamCreated {
-        stream: stream.key(),
-        sender: stream.sender,
-        recipient: stream.recipient,
-        mint: stream.mint,
-        amount: stream.amount,
-        start_time: stream.start_time,
-        end_time: stream.end_time,
-        cliff_time: stream.cliff_time,
-        stream_id: stream.stream_id.clone(),
-    });
+        create_master_edition(
+            token_metadata_program.clone(),
+            master_edition.clone(),
+            nft_mint.clone(),
+            stream.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            metadata.clone(),
+            nft_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+            signer_seeds,
+        )?;
+
+        stream.nft_recipient = true;
+
+        emit!(StreamNftMinted {
+            stream: stream_key,
+            nft_mint: nft_mint.key(),
+            recipient: stream.recipient,
+        });
+    }
+
+    // Transfer tokens from sender to escrow and the platform fee to the treasury
+    for (destination, transfer_amount) in [
+        (ctx.accounts.escrow_token_account.to_account_info(), deposit_amount),
+        (ctx.accounts.fee_vault.to_account_info(), fee_amount),
+    ] {
+        if transfer_amount == 0 {
+            continue;
+        }
+
+        match ctx.accounts.sender_token_account.as_ref() {
+            Some(sender_token_account) => transfer_tokens(
+                &ctx.accounts.token_program,
+                sender_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                destination,
+                ctx.accounts.sender.to_account_info(),
+                ctx.remaining_accounts,
+                transfer_amount,
+                &[],
+            )?,
+            None => wrap_sol(
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                ctx.accounts.sender.to_account_info(),
+                destination,
+                transfer_amount,
+            )?,
+        }
+    }
+
+    if fee_amount > 0 {
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, fee_amount)?)?;
+    }
+
+    emit!(StreamCreated::new(stream.key(), stream, fee_amount));
 
     Ok(())
 }
@@ -138,6 +539,50 @@ pub struct StreamCreated {
     pub start_time: i64,
     pub end_time: i64,
     pub cliff_time: Option<i64>,
+    pub initial_unlock_amount: u64,
+    pub cliff_unlock_amount: u64,
+    pub fee_amount: u64,
     pub stream_id: String,
 }
-```
\ No newline at end of file
+
+#[event]
+pub struct StreamNftMinted {
+    pub stream: Pubkey,
+    pub nft_mint: Pubkey,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct VestingScheduleCreated {
+    pub stream: Pubkey,
+    pub vesting_schedule: Pubkey,
+    pub checkpoint_count: u8,
+    pub interpolate: bool,
+}
+
+#[event]
+pub struct MilestoneScheduleCreated {
+    pub stream: Pubkey,
+    pub milestone_schedule: Pubkey,
+    pub approver: Pubkey,
+    pub milestone_count: u8,
+}
+
+impl StreamCreated {
+    pub(crate) fn new(stream_key: Pubkey, stream: &Stream, fee_amount: u64) -> Self {
+        Self {
+            stream: stream_key,
+            sender: stream.sender,
+            recipient: stream.recipient,
+            mint: stream.mint,
+            amount: stream.amount,
+            start_time: stream.start_time,
+            end_time: stream.end_time,
+            cliff_time: stream.cliff_time,
+            initial_unlock_amount: stream.initial_unlock_amount,
+            cliff_unlock_amount: stream.cliff_unlock_amount,
+            fee_amount,
+            stream_id: stream.stream_id.clone(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+use crate::instructions::create_stream::{self, *};
+
+#[derive(Accounts)]
+pub struct CreateStreamFromTemplate<'info> {
+    #[account(
+        seeds = [b"stream_template", template.sender.as_ref(), template.template_id.as_bytes()],
+        bump = template.bump,
+        constraint = template.sender == create_stream.sender.key() @ StreamFlowError::TemplateParameterMismatch,
+    )]
+    pub template: Account<'info, StreamTemplate>,
+
+    pub create_stream: CreateStream<'info>,
+}
+
+pub fn create_stream_from_template<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateStreamFromTemplate<'info>>,
+    stream_id: String,
+    amount: u64,
+    start_time: i64,
+) -> Result<()> {
+    let template = &ctx.accounts.template;
+    let config = template.config.starting_at(start_time)?;
+    config.validate()?;
+
+    let params = StreamParams {
+        stream_id,
+        amount,
+        start_time: config.start_time,
+        end_time: config.end_time,
+        cliff_time: config.cliff_time,
+        stream_type: config.stream_type,
+        release_frequency: config.withdraw_frequency,
+        rate_per_second: 0,
+        initial_unlock: UnlockAmount::Amount(0),
+        cliff_unlock: UnlockAmount::Amount(0),
+        cancelable_by_sender: config.can_cancel,
+        cancelable_by_recipient: config.cancelable_by_recipient,
+        transferable_by_sender: config.transferable_by_sender,
+        transferable_by_recipient: config.transferable_by_recipient,
+        can_pause: config.can_pause,
+        automatic_withdrawal: config.automatic_withdrawal,
+        withdrawal_public: config.withdrawal_public,
+    };
+
+    let template_key = template.key();
+    let stream_key = ctx.accounts.create_stream.stream.key();
+    create_stream::handler(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.create_stream,
+            ctx.remaining_accounts,
+            ctx.bumps.create_stream,
+        ),
+        params,
+        None,
+        None,
+    )?;
+
+    emit!(StreamCreatedFromTemplate {
+        stream: stream_key,
+        template: template_key,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamCreatedFromTemplate {
+    pub stream: Pubkey,
+    pub template: Pubkey,
+}
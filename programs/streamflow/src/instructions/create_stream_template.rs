@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+#[instruction(template_id: String)]
+pub struct CreateStreamTemplate<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = StreamTemplate::LEN,
+        seeds = [b"stream_template", sender.key().as_ref(), template_id.as_bytes()],
+        bump
+    )]
+    pub template: Account<'info, StreamTemplate>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_stream_template(
+    ctx: Context<CreateStreamTemplate>,
+    template_id: String,
+    config: StreamConfig,
+) -> Result<()> {
+    require!(template_id.len() <= MAX_STREAM_ID_LEN, StreamFlowError::StreamIdTooLong);
+    StreamTemplate::validate_config(&config, Clock::get()?.unix_timestamp)?;
+
+    let template = &mut ctx.accounts.template;
+    template.sender = ctx.accounts.sender.key();
+    template.template_id = template_id;
+    template.bump = ctx.bumps.template;
+    template.config = config;
+
+    emit!(StreamTemplateCreated {
+        template: template.key(),
+        sender: template.sender,
+        template_id: template.template_id.clone(),
+        stream_type: template.config.stream_type,
+        duration: template.config.duration(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamTemplateCreated {
+    pub template: Pubkey,
+    pub sender: Pubkey,
+    pub template_id: String,
+    pub stream_type: StreamType,
+    pub duration: i64,
+}
@@ -0,0 +1,264 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, InitializeAccount3, Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::create_pda_account;
+use crate::instructions::create_stream::{build_stream, StreamAccounts, StreamCreated};
+use crate::token::{amount_after_transfer_fee, is_native_mint, token_account_len, transfer_tokens, wrap_sol};
+
+/// One stream to open in a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchStreamEntry {
+    pub recipient: Pubkey,
+    pub params: StreamParams,
+}
+
+/// Opens several streams from one sender in a single instruction.
+///
+/// `remaining_accounts` starts with a `[stream, escrow]` pair for each entry,
+/// in entry order, both writable and not yet created. Any accounts after the
+/// pairs are passed on as transfer-hook accounts.
+#[derive(Accounts)]
+pub struct CreateStreamsBatch<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// Omit to pay a wrapped SOL batch in lamports
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == sender.key(),
+        constraint = sender_token_account.mint == mint.key()
+    )]
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"mint_registry"],
+        bump = mint_registry.bump,
+    )]
+    pub mint_registry: Account<'info, MintRegistry>,
+
+    /// CHECK: Only the address is constrained, an empty account means the mint is not listed
+    #[account(
+        seeds = [b"mint_entry", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_entry: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_streams_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateStreamsBatch<'info>>,
+    entries: Vec<BatchStreamEntry>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(!entries.is_empty(), StreamFlowError::InvalidBatchOperation);
+    require!(
+        entries.len() <= ctx.accounts.config.effective_max_batch_size() as usize,
+        StreamFlowError::BatchOperationLimitExceeded
+    );
+
+    let stream_accounts_len = entries.len() * 2;
+    require!(
+        ctx.remaining_accounts.len() >= stream_accounts_len,
+        StreamFlowError::InvalidBatchOperation
+    );
+    let (stream_accounts, hook_accounts) = ctx.remaining_accounts.split_at(stream_accounts_len);
+
+    ctx.accounts.mint_registry.check_mint(
+        &ctx.accounts.mint,
+        MintEntry::is_listed(&ctx.accounts.mint_entry),
+    )?;
+
+    // Everything is checked against the sender's balance up front so the batch fails as a whole
+    let mut total_deposit: u64 = 0;
+    let mut total_fee: u64 = 0;
+    for entry in &entries {
+        let amount = amount_after_transfer_fee(&ctx.accounts.mint, entry.params.amount)?;
+        let fee_amount = FeeConfig::calculate_fee(amount, ctx.accounts.config.fee_config.platform_fee_rate)?;
+        total_deposit = total_deposit
+            .checked_add(entry.params.amount)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        total_fee = total_fee
+            .checked_add(fee_amount)
+            .ok_or(StreamFlowError::FeeCalculationError)?;
+    }
+    let total_amount = total_deposit
+        .checked_add(total_fee)
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+
+    let sender_balance = match ctx.accounts.sender_token_account.as_ref() {
+        Some(sender_token_account) => sender_token_account.amount,
+        None => {
+            require!(is_native_mint(&ctx.accounts.mint.key()), StreamFlowError::InvalidTokenAccount);
+            ctx.accounts.sender.lamports()
+        },
+    };
+    require!(
+        sender_balance >= total_amount,
+        StreamFlowError::InsufficientTokenBalance
+    );
+
+    let sender_key = ctx.accounts.sender.key();
+    let escrow_len = token_account_len(&ctx.accounts.mint)?;
+    let stream_count = entries.len() as u8;
+
+    for (entry, accounts) in entries.into_iter().zip(stream_accounts.chunks(2)) {
+        let (stream_info, escrow_info) = (&accounts[0], &accounts[1]);
+        let BatchStreamEntry { recipient, params } = entry;
+        let deposit_amount = params.amount;
+
+        // Seeds longer than 32 bytes cannot be derived, reject them before deriving
+        require!(
+            params.stream_id.len() <= MAX_STREAM_ID_LEN,
+            StreamFlowError::StreamIdTooLong
+        );
+        let (stream_key, bump) = Pubkey::find_program_address(
+            &[b"stream", sender_key.as_ref(), params.stream_id.as_bytes()],
+            ctx.program_id,
+        );
+        let (escrow_key, escrow_bump) = Pubkey::find_program_address(
+            &[b"escrow", stream_key.as_ref()],
+            ctx.program_id,
+        );
+        require!(
+            stream_info.key() == stream_key && escrow_info.key() == escrow_key,
+            StreamFlowError::InvalidBatchOperation
+        );
+
+        let amount = amount_after_transfer_fee(&ctx.accounts.mint, deposit_amount)?;
+        let fee_amount = FeeConfig::calculate_fee(amount, ctx.accounts.config.fee_config.platform_fee_rate)?;
+        let stream_id = params.stream_id.clone();
+        let new_stream = build_stream(
+            params,
+            amount,
+            StreamAccounts {
+                sender: sender_key,
+                recipient,
+                mint: ctx.accounts.mint.key(),
+                escrow_token_account: escrow_key,
+                bump,
+                escrow_bump,
+            },
+            None,
+            current_time,
+        )?;
+
+        create_pda_account(
+            &ctx.accounts.system_program,
+            ctx.accounts.sender.to_account_info(),
+            stream_info.clone(),
+            Stream::LEN,
+            ctx.program_id,
+            &[b"stream", sender_key.as_ref(), stream_id.as_bytes(), &[bump]],
+        )?;
+        new_stream.try_serialize(&mut &mut stream_info.try_borrow_mut_data()?[..])?;
+
+        create_pda_account(
+            &ctx.accounts.system_program,
+            ctx.accounts.sender.to_account_info(),
+            escrow_info.clone(),
+            escrow_len,
+            ctx.accounts.token_program.key,
+            &[b"escrow", stream_key.as_ref(), &[escrow_bump]],
+        )?;
+        token_interface::initialize_account3(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            InitializeAccount3 {
+                account: escrow_info.clone(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: stream_info.clone(),
+            },
+        ))?;
+
+        match ctx.accounts.sender_token_account.as_ref() {
+            Some(sender_token_account) => transfer_tokens(
+                &ctx.accounts.token_program,
+                sender_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                escrow_info.clone(),
+                ctx.accounts.sender.to_account_info(),
+                hook_accounts,
+                deposit_amount,
+                &[],
+            )?,
+            None => wrap_sol(
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                ctx.accounts.sender.to_account_info(),
+                escrow_info.clone(),
+                deposit_amount,
+            )?,
+        }
+
+        emit!(StreamCreated::new(stream_key, &new_stream, fee_amount));
+    }
+
+    // Platform fees for the whole batch move to the treasury in one transfer
+    if total_fee > 0 {
+        match ctx.accounts.sender_token_account.as_ref() {
+            Some(sender_token_account) => transfer_tokens(
+                &ctx.accounts.token_program,
+                sender_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                ctx.accounts.fee_vault.to_account_info(),
+                ctx.accounts.sender.to_account_info(),
+                hook_accounts,
+                total_fee,
+                &[],
+            )?,
+            None => wrap_sol(
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                ctx.accounts.sender.to_account_info(),
+                ctx.accounts.fee_vault.to_account_info(),
+                total_fee,
+            )?,
+        }
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, total_fee)?)?;
+    }
+
+    emit!(BatchStreamsCreated {
+        sender: sender_key,
+        mint: ctx.accounts.mint.key(),
+        stream_count,
+        total_amount: total_deposit,
+        fee_amount: total_fee,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct BatchStreamsCreated {
+    pub sender: Pubkey,
+    pub mint: Pubkey,
+    pub stream_count: u8,
+    pub total_amount: u64,
+    pub fee_amount: u64,
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::{close_pda_account, create_pda_account};
+use crate::instructions::withdraw_treasury::sweep_treasury;
+
+/// Permissionless: the change was public for the whole delay, so anyone may apply it once it expires.
+/// This lets changes queued by governance run without an admin key.
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"config_proposal".as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = proposal.executed_at == 0 @ StreamFlowError::TimelockAlreadyExecuted,
+        constraint = proposal.cancelled_at == 0 @ StreamFlowError::InvalidTimelockProposal,
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    /// CHECK: Created here if still empty, deserialized in the handler otherwise.
+    /// Required for `ConfigChange::MintRegistry`
+    #[account(
+        mut,
+        seeds = [b"mint_registry"],
+        bump,
+    )]
+    pub mint_registry: Option<AccountInfo<'info>>,
+
+    /// CHECK: `[b"mint_entry", mint]` PDA of the mint being listed or removed, checked in the handler.
+    /// Required for `ConfigChange::AddMintEntry` and `ConfigChange::RemoveMintEntry`
+    #[account(mut)]
+    pub mint_entry: Option<AccountInfo<'info>>,
+
+    /// Required for `ConfigChange::WithdrawTreasury`
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Required for `ConfigChange::WithdrawTreasury`
+    #[account(mut)]
+    pub fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account of the fee recipient, required for `ConfigChange::WithdrawTreasury`
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for `ConfigChange::WithdrawTreasury`
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Required for `ConfigChange::WithdrawTreasury`
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Pays for new mint entries and the mint registry, and receives the rent of removed entries
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn execute_config_change<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteConfigChange<'info>>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.proposal;
+    require!(current_time >= proposal.eta, StreamFlowError::TimelockNotExpired);
+
+    let config = &mut ctx.accounts.config;
+    match proposal.change.clone() {
+        ConfigChange::FeeConfig { fee_config } => config.fee_config = fee_config,
+        ConfigChange::Admin { admin } => config.admin = admin,
+        ConfigChange::CrankTipRate { crank_tip_rate } => config.crank_tip_rate = crank_tip_rate,
+        ConfigChange::MaxBatchSize { max_batch_size } => config.max_batch_size = max_batch_size,
+        ConfigChange::TimelockDelay { timelock_delay } => config.timelock_delay = timelock_delay,
+        ConfigChange::PauseGuardian { pause_guardian } => config.pause_guardian = pause_guardian,
+        ConfigChange::MintRegistry { mode, max_decimals, reject_freezable } => {
+            let mint_registry = ctx.accounts.mint_registry.as_ref()
+                .ok_or(StreamFlowError::InvalidTimelockProposal)?;
+            let bump = ctx.bumps.mint_registry;
+
+            // Once the admin is governance, nobody can sign `initialize_mint_registry`
+            if mint_registry.data_is_empty() {
+                create_pda_account(
+                    &ctx.accounts.system_program,
+                    ctx.accounts.payer.to_account_info(),
+                    mint_registry.clone(),
+                    MintRegistry::LEN,
+                    ctx.program_id,
+                    &[b"mint_registry", &[bump]],
+                )?;
+            } else {
+                require_keys_eq!(*mint_registry.owner, *ctx.program_id, StreamFlowError::InvalidAccountState);
+                MintRegistry::try_deserialize(&mut &mint_registry.try_borrow_data()?[..])?;
+            }
+
+            MintRegistry { mode, max_decimals, reject_freezable, bump }
+                .try_serialize(&mut &mut mint_registry.try_borrow_mut_data()?[..])?;
+        },
+        ConfigChange::WithdrawTreasury { mint, amount } => {
+            let (Some(treasury), Some(fee_vault), Some(destination_token_account), Some(mint_account), Some(token_program)) = (
+                ctx.accounts.treasury.as_mut(),
+                ctx.accounts.fee_vault.as_ref(),
+                ctx.accounts.destination_token_account.as_ref(),
+                ctx.accounts.mint.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return err!(StreamFlowError::InvalidTimelockProposal);
+            };
+
+            require_keys_eq!(treasury.mint, mint, StreamFlowError::InvalidTimelockProposal);
+            require_keys_eq!(mint_account.key(), mint, StreamFlowError::InvalidTokenMint);
+            require_keys_eq!(fee_vault.key(), treasury.vault, StreamFlowError::InvalidTokenAccount);
+            require_keys_eq!(destination_token_account.mint, mint, StreamFlowError::InvalidTokenMint);
+            require_keys_eq!(
+                destination_token_account.owner,
+                config.fee_config.fee_recipient,
+                StreamFlowError::InvalidFeeRecipient
+            );
+
+            sweep_treasury(
+                treasury,
+                fee_vault,
+                destination_token_account,
+                mint_account,
+                token_program,
+                ctx.remaining_accounts,
+                amount,
+            )?;
+        },
+        ConfigChange::AddMintEntry { mint } => {
+            let mint_entry = mint_entry_account(&ctx.accounts.mint_entry, &mint, ctx.program_id)?;
+            let (_, bump) = Pubkey::find_program_address(&[b"mint_entry", mint.as_ref()], ctx.program_id);
+
+            create_pda_account(
+                &ctx.accounts.system_program,
+                ctx.accounts.payer.to_account_info(),
+                mint_entry.clone(),
+                MintEntry::LEN,
+                ctx.program_id,
+                &[b"mint_entry", mint.as_ref(), &[bump]],
+            )?;
+            MintEntry { mint, bump }.try_serialize(&mut &mut mint_entry.try_borrow_mut_data()?[..])?;
+        },
+        ConfigChange::RemoveMintEntry { mint } => {
+            let mint_entry = mint_entry_account(&ctx.accounts.mint_entry, &mint, ctx.program_id)?;
+            require!(MintEntry::is_listed(mint_entry), StreamFlowError::InvalidAccountState);
+
+            close_pda_account(mint_entry, &ctx.accounts.payer.to_account_info())?;
+        },
+    }
+
+    proposal.executed_at = current_time;
+
+    emit!(ConfigChangeExecuted {
+        proposal: proposal.key(),
+        index: proposal.index,
+        change: proposal.change.clone(),
+        executed_by: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
+
+/// The `mint_entry` account, checked to be the entry PDA of `mint`
+fn mint_entry_account<'a, 'info>(
+    mint_entry: &'a Option<AccountInfo<'info>>,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    let mint_entry = mint_entry.as_ref().ok_or(StreamFlowError::InvalidTimelockProposal)?;
+    let (expected_key, _) = Pubkey::find_program_address(&[b"mint_entry", mint.as_ref()], program_id);
+    require_keys_eq!(mint_entry.key(), expected_key, StreamFlowError::InvalidTimelockProposal);
+    Ok(mint_entry)
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub change: ConfigChange,
+    pub executed_by: Pubkey,
+}
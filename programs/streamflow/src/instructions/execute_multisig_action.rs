@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::InstructionData;
+
+use crate::state::*;
+use crate::error::*;
+use crate::program::Streamflow;
+
+/// Runs an approved proposal by calling back into this program with the
+/// multisig signer PDA signing as the stream's sender.
+///
+/// `remaining_accounts` are the accounts of the target instruction, in its
+/// order, starting with the stream. The multisig signer PDA is passed in the
+/// sender or authority position and is marked as signer here.
+#[derive(Accounts)]
+pub struct ExecuteMultisigAction<'info> {
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig_proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = multisig,
+        constraint = !proposal.executed @ StreamFlowError::InvalidAccountState,
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    /// CHECK: Signer PDA that acts as stream sender, holds no data
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump = multisig.signer_bump,
+    )]
+    pub multisig_signer: AccountInfo<'info>,
+
+    pub executor: Signer<'info>,
+
+    pub streamflow_program: Program<'info, Streamflow>,
+}
+
+pub fn execute_multisig_action<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteMultisigAction<'info>>,
+) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    multisig.signer_index(&ctx.accounts.executor.key())?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        proposal.approval_count() >= multisig.threshold as usize,
+        StreamFlowError::InsufficientMultisigSignatures
+    );
+    require!(
+        ctx.remaining_accounts.first().map(|account| account.key()) == Some(proposal.stream),
+        StreamFlowError::StreamConfigurationMismatch
+    );
+
+    // Written to the account before the call so the proposal cannot be replayed from within it
+    proposal.executed = true;
+    proposal.exit(&crate::ID)?;
+
+    let data = match proposal.action.clone() {
+        MultisigAction::CreateStream { params } => crate::instruction::InitializeStream {
+            stream_id: params.stream_id,
+            amount: params.amount,
+            start_time: params.start_time,
+            end_time: params.end_time,
+            cliff_time: params.cliff_time,
+            stream_type: params.stream_type,
+            release_frequency: params.release_frequency,
+            rate_per_second: params.rate_per_second,
+            initial_unlock: params.initial_unlock,
+            cliff_unlock: params.cliff_unlock,
+            cancelable_by_sender: params.cancelable_by_sender,
+            cancelable_by_recipient: params.cancelable_by_recipient,
+            transferable_by_sender: params.transferable_by_sender,
+            transferable_by_recipient: params.transferable_by_recipient,
+            can_pause: params.can_pause,
+            automatic_withdrawal: params.automatic_withdrawal,
+            withdrawal_public: params.withdrawal_public,
+            schedule: None,
+            position_nft: None,
+        }
+        .data(),
+        MultisigAction::CancelStream => crate::instruction::CancelStream {}.data(),
+        MultisigAction::UpdateStream {
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            transferable_by_sender,
+            transferable_by_recipient,
+        } => crate::instruction::UpdateStream {
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            transferable_by_sender,
+            transferable_by_recipient,
+        }
+        .data(),
+        MultisigAction::TransferStream { new_recipient } => {
+            crate::instruction::TransferStream { new_recipient }.data()
+        },
+    };
+
+    let multisig_signer = ctx.accounts.multisig_signer.key();
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == multisig_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let instruction = Instruction {
+        program_id: crate::ID,
+        accounts,
+        data,
+    };
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.streamflow_program.to_account_info());
+
+    let multisig_key = multisig.key();
+    let signer_seeds = &[b"multisig_signer", multisig_key.as_ref(), &[multisig.signer_bump]];
+    invoke_signed(&instruction, &account_infos, &[&signer_seeds[..]])?;
+
+    emit!(MultisigActionExecuted {
+        multisig: multisig_key,
+        proposal: proposal.key(),
+        executor: ctx.accounts.executor.key(),
+        stream: proposal.stream,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MultisigActionExecuted {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+    pub stream: Pubkey,
+}
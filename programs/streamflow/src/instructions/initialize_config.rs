@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+use crate::program::Streamflow;
+
+/// Only the program's upgrade authority can create the config and become its admin
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ StreamFlowError::Unauthorized)]
+    pub program: Program<'info, Streamflow>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ StreamFlowError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    pause_guardian: Pubkey,
+    fee_config: FeeConfig,
+    timelock_delay: i64,
+) -> Result<()> {
+    fee_config.validate()?;
+    Config::validate_timelock_delay(timelock_delay)?;
+
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.fee_config = fee_config;
+    config.bump = ctx.bumps.config;
+    config.pause_guardian = pause_guardian;
+    config.emergency_paused = false;
+    config.pause_exemptions = 0;
+    config.max_batch_size = Config::MAX_BATCH_SIZE;
+    config.timelock_delay = timelock_delay;
+    config.proposal_count = 0;
+
+    emit!(ConfigInitialized {
+        config: config.key(),
+        admin: config.admin,
+        pause_guardian,
+        fee_config: config.fee_config.clone(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub pause_guardian: Pubkey,
+    pub fee_config: FeeConfig,
+}
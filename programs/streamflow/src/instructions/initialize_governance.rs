@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitializeGovernance<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Governance::LEN,
+        seeds = [b"governance"],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = governance,
+        token::token_program = token_program,
+        seeds = [b"governance_vault"],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn initialize_governance(
+    ctx: Context<InitializeGovernance>,
+    quorum_votes: u64,
+    voting_period: i64,
+) -> Result<()> {
+    Governance::validate_params(quorum_votes, voting_period)?;
+
+    let governance = &mut ctx.accounts.governance;
+    governance.mint = ctx.accounts.mint.key();
+    governance.vault = ctx.accounts.vault.key();
+    governance.quorum_votes = quorum_votes;
+    governance.voting_period = voting_period;
+    governance.proposal_count = 0;
+    governance.bump = ctx.bumps.governance;
+    governance.vault_bump = ctx.bumps.vault;
+
+    emit!(GovernanceInitialized {
+        governance: governance.key(),
+        mint: governance.mint,
+        quorum_votes,
+        voting_period,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GovernanceInitialized {
+    pub governance: Pubkey,
+    pub mint: Pubkey,
+    pub quorum_votes: u64,
+    pub voting_period: i64,
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct InitializeMintRegistry<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MintRegistry::LEN,
+        seeds = [b"mint_registry"],
+        bump
+    )]
+    pub mint_registry: Account<'info, MintRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_mint_registry(
+    ctx: Context<InitializeMintRegistry>,
+    mode: MintListMode,
+    max_decimals: u8,
+    reject_freezable: bool,
+) -> Result<()> {
+    let mint_registry = &mut ctx.accounts.mint_registry;
+    mint_registry.mode = mode;
+    mint_registry.max_decimals = max_decimals;
+    mint_registry.reject_freezable = reject_freezable;
+    mint_registry.bump = ctx.bumps.mint_registry;
+
+    emit!(MintRegistryUpdated {
+        mint_registry: mint_registry.key(),
+        mode,
+        max_decimals,
+        reject_freezable,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MintRegistryUpdated {
+    pub mint_registry: Pubkey,
+    pub mode: MintListMode,
+    pub max_decimals: u8,
+    pub reject_freezable: bool,
+}
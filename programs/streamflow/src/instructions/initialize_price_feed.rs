@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializePriceFeed<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PriceFeed::LEN,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_price_feed(ctx: Context<InitializePriceFeed>, expo: i32) -> Result<()> {
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.authority = ctx.accounts.authority.key();
+    price_feed.price = 0;
+    price_feed.expo = expo;
+    price_feed.publish_time = 0;
+
+    emit!(PriceFeedInitialized {
+        price_feed: price_feed.key(),
+        authority: price_feed.authority,
+        expo,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PriceFeedInitialized {
+    pub price_feed: Pubkey,
+    pub authority: Pubkey,
+    pub expo: i32,
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+
+/// Permissionless: the treasury's address and authority are fixed, so anyone may pay to create it
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Treasury::LEN,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+        seeds = [b"fee_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.mint = ctx.accounts.mint.key();
+    treasury.vault = ctx.accounts.fee_vault.key();
+    treasury.total_collected = 0;
+    treasury.total_withdrawn = 0;
+    treasury.bump = ctx.bumps.treasury;
+    treasury.vault_bump = ctx.bumps.fee_vault;
+
+    emit!(TreasuryInitialized {
+        treasury: treasury.key(),
+        mint: treasury.mint,
+        fee_vault: treasury.vault,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryInitialized {
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub fee_vault: Pubkey,
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::state::*;
+use crate::error::*;
+
+/// Permissionless: rewrites a stream created with layout version 1 in the current layout.
+///
+/// Version 1 accounts no longer deserialize as `Stream`, so every other
+/// instruction rejects them until they are migrated. `payer` covers the rent
+/// of the larger account.
+#[derive(Accounts)]
+pub struct MigrateStream<'info> {
+    /// CHECK: Decoded by hand as `StreamV1`, its address is checked against the decoded seeds
+    #[account(mut, owner = crate::ID)]
+    pub stream: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_stream(ctx: Context<MigrateStream>) -> Result<()> {
+    let stream_info = &ctx.accounts.stream;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let old_stream = {
+        let data = stream_info.try_borrow_data()?;
+        require!(
+            data.len() > 8 && data[..8] == Stream::DISCRIMINATOR,
+            StreamFlowError::AccountDiscriminatorMismatch
+        );
+        require!(data[8] == StreamV1::VERSION, StreamFlowError::VersionMismatch);
+        StreamV1::deserialize(&mut &data[8..])?
+    };
+
+    let expected_stream = Pubkey::create_program_address(
+        &[b"stream", old_stream.sender.as_ref(), old_stream.stream_id.as_bytes(), &[old_stream.bump]],
+        ctx.program_id,
+    )
+    .map_err(|_| StreamFlowError::InvalidAccountState)?;
+    require_keys_eq!(stream_info.key(), expected_stream, StreamFlowError::InvalidAccountState);
+
+    let new_stream = old_stream.migrate(current_time)?;
+
+    let required_lamports = Rent::get()?
+        .minimum_balance(Stream::LEN)
+        .saturating_sub(stream_info.lamports());
+    if required_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: stream_info.clone(),
+                },
+            ),
+            required_lamports,
+        )?;
+    }
+    stream_info.realloc(Stream::LEN, true)?;
+    new_stream.try_serialize(&mut &mut stream_info.try_borrow_mut_data()?[..])?;
+
+    emit!(StreamMigrated {
+        stream: stream_info.key(),
+        from_version: StreamV1::VERSION,
+        to_version: STREAM_VERSION,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamMigrated {
+    pub stream: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
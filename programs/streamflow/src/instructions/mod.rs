@@ -0,0 +1,73 @@
+pub mod approve_milestone;
+pub mod approve_multisig_action;
+pub mod cancel_config_change;
+pub mod cancel_stream;
+pub mod cast_vote;
+pub mod close_stream;
+pub mod crank_withdraw;
+pub mod create_governance_proposal;
+pub mod create_multisig;
+pub mod create_price_condition;
+pub mod create_stream;
+pub mod create_stream_from_template;
+pub mod create_stream_template;
+pub mod create_streams_batch;
+pub mod execute_config_change;
+pub mod execute_multisig_action;
+pub mod initialize_config;
+pub mod initialize_governance;
+pub mod initialize_mint_registry;
+pub mod initialize_price_feed;
+pub mod initialize_treasury;
+pub mod migrate_stream;
+pub mod pause_stream;
+pub mod propose_multisig_action;
+pub mod queue_config_change;
+pub mod queue_governance_proposal;
+pub mod relinquish_vote;
+pub mod resume_stream;
+pub mod set_emergency_pause;
+pub mod set_payout_destination;
+pub mod set_withdraw_delegate;
+pub mod top_up_stream;
+pub mod unlock_price_milestone;
+pub mod update_price_feed;
+pub mod withdraw;
+pub mod withdraw_treasury;
+
+pub use approve_milestone::*;
+pub use approve_multisig_action::*;
+pub use cancel_config_change::*;
+pub use cancel_stream::*;
+pub use cast_vote::*;
+pub use close_stream::*;
+pub use crank_withdraw::*;
+pub use create_governance_proposal::*;
+pub use create_multisig::*;
+pub use create_price_condition::*;
+pub use create_stream::*;
+pub use create_stream_from_template::*;
+pub use create_stream_template::*;
+pub use create_streams_batch::*;
+pub use execute_config_change::*;
+pub use execute_multisig_action::*;
+pub use initialize_config::*;
+pub use initialize_governance::*;
+pub use initialize_mint_registry::*;
+pub use initialize_price_feed::*;
+pub use initialize_treasury::*;
+pub use migrate_stream::*;
+pub use pause_stream::*;
+pub use propose_multisig_action::*;
+pub use queue_config_change::*;
+pub use queue_governance_proposal::*;
+pub use relinquish_vote::*;
+pub use resume_stream::*;
+pub use set_emergency_pause::*;
+pub use set_payout_destination::*;
+pub use set_withdraw_delegate::*;
+pub use top_up_stream::*;
+pub use unlock_price_milestone::*;
+pub use update_price_feed::*;
+pub use withdraw::*;
+pub use withdraw_treasury::*;
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct PauseStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.sender == sender.key() @ StreamFlowError::OnlySenderCanPause,
+        constraint = stream.can_pause @ StreamFlowError::CannotPauseStream,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub sender: Signer<'info>,
+}
+
+pub fn pause_stream(ctx: Context<PauseStream>) -> Result<()> {
+    let stream = &mut ctx.accounts.stream;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(stream.status != StreamStatus::Paused, StreamFlowError::StreamAlreadyPaused);
+    require!(stream.status == StreamStatus::Active, StreamFlowError::StreamNotActive);
+    require!(current_time < stream.end_time, StreamFlowError::StreamEnded);
+
+    stream.status = StreamStatus::Paused;
+    stream.paused_at = current_time;
+
+    emit!(StreamPaused {
+        stream: stream.key(),
+        paused_by: ctx.accounts.sender.key(),
+        paused_at: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamPaused {
+    pub stream: Pubkey,
+    pub paused_by: Pubkey,
+    pub paused_at: i64,
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ProposeMultisigAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = MultisigProposal::LEN,
+        seeds = [b"multisig_proposal", multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn propose_multisig_action(
+    ctx: Context<ProposeMultisigAction>,
+    stream: Pubkey,
+    action: MultisigAction,
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let proposer_index = multisig.signer_index(&ctx.accounts.proposer.key())?;
+
+    // A new stream must be the one the multisig signer will derive for this id
+    if let MultisigAction::CreateStream { params } = &action {
+        require!(params.stream_id.len() <= MAX_STREAM_ID_LEN, StreamFlowError::StreamIdTooLong);
+        // No action sets up or drives a schedule or milestones, so such a stream would be stuck
+        require!(
+            !matches!(params.stream_type, StreamType::Schedule | StreamType::Milestone),
+            StreamFlowError::InvalidStreamType
+        );
+        let (multisig_signer, _) = Pubkey::find_program_address(
+            &[b"multisig_signer", multisig.key().as_ref()],
+            ctx.program_id,
+        );
+        let (expected_stream, _) = Pubkey::find_program_address(
+            &[b"stream", multisig_signer.as_ref(), params.stream_id.as_bytes()],
+            ctx.program_id,
+        );
+        require!(stream == expected_stream, StreamFlowError::StreamConfigurationMismatch);
+    }
+
+    // Proposing counts as the proposer's approval
+    let mut approvals = vec![false; multisig.signers.len()];
+    approvals[proposer_index] = true;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.multisig = multisig.key();
+    proposal.index = multisig.proposal_count;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.stream = stream;
+    proposal.action = action;
+    proposal.approvals = approvals;
+    proposal.executed = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    multisig.proposal_count = multisig.proposal_count
+        .checked_add(1)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    emit!(MultisigActionProposed {
+        multisig: proposal.multisig,
+        proposal: proposal.key(),
+        index: proposal.index,
+        proposer: proposal.proposer,
+        stream,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MultisigActionProposed {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub stream: Pubkey,
+}
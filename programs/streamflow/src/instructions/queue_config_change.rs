@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ConfigProposal::LEN,
+        seeds = [b"config_proposal".as_ref(), &config.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, ConfigProposal>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn queue_config_change(ctx: Context<QueueConfigChange>, change: ConfigChange) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    let eta = proposal.queue(
+        &mut ctx.accounts.config,
+        change,
+        ctx.accounts.admin.key(),
+        ctx.bumps.proposal,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    emit!(ConfigChangeQueued {
+        proposal: proposal.key(),
+        index: proposal.index,
+        change: proposal.change.clone(),
+        eta,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub change: ConfigChange,
+    pub eta: i64,
+}
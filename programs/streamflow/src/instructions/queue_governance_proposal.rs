@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+/// Permissionless: a passed proposal is queued in the timelock with the governance PDA as proposer
+#[derive(Accounts)]
+pub struct QueueGovernanceProposal<'info> {
+    #[account(
+        seeds = [b"governance"],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"governance_proposal".as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = !proposal.queued @ StreamFlowError::InvalidGovernanceProposal,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// Governance only acts once the admin role has been handed over to it
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin == governance.key() @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ConfigProposal::LEN,
+        seeds = [b"config_proposal".as_ref(), &config.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub config_proposal: Account<'info, ConfigProposal>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn queue_governance_proposal(ctx: Context<QueueGovernanceProposal>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.check_passed(ctx.accounts.governance.quorum_votes, current_time)?;
+    proposal.queued = true;
+
+    let config_proposal = &mut ctx.accounts.config_proposal;
+    let eta = config_proposal.queue(
+        &mut ctx.accounts.config,
+        proposal.change.clone(),
+        ctx.accounts.governance.key(),
+        ctx.bumps.config_proposal,
+        current_time,
+    )?;
+
+    emit!(GovernanceProposalQueued {
+        proposal: proposal.key(),
+        config_proposal: config_proposal.key(),
+        yes_votes: proposal.yes_votes,
+        no_votes: proposal.no_votes,
+        eta,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GovernanceProposalQueued {
+    pub proposal: Pubkey,
+    pub config_proposal: Pubkey,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub eta: i64,
+}
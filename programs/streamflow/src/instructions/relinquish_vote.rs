@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::token::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct RelinquishVote<'info> {
+    #[account(
+        seeds = [b"governance"],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        seeds = [b"governance_proposal".as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(
+        mut,
+        close = voter,
+        seeds = [b"vote_record", proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+        has_one = proposal,
+        has_one = voter,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ StreamFlowError::InvalidTokenAccount,
+        constraint = voter_token_account.mint == governance.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub voter_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = governance.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = governance.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn relinquish_vote<'info>(ctx: Context<'_, '_, '_, 'info, RelinquishVote<'info>>) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp >= ctx.accounts.proposal.voting_ends_at,
+        StreamFlowError::GovernanceVotingPeriodActive
+    );
+
+    let votes = ctx.accounts.vote_record.votes;
+    let seeds = &[b"governance".as_ref(), &[ctx.accounts.governance.bump]];
+    transfer_tokens(
+        &ctx.accounts.token_program,
+        ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.voter_token_account.to_account_info(),
+        ctx.accounts.governance.to_account_info(),
+        ctx.remaining_accounts,
+        votes,
+        &[&seeds[..]],
+    )?;
+
+    emit!(VoteRelinquished {
+        proposal: ctx.accounts.proposal.key(),
+        voter: ctx.accounts.voter.key(),
+        votes,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct VoteRelinquished {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub votes: u64,
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ResumeStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.sender == sender.key() @ StreamFlowError::OnlySenderCanResume,
+        constraint = stream.status == StreamStatus::Paused @ StreamFlowError::StreamNotPaused,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub sender: Signer<'info>,
+}
+
+pub fn resume_stream(ctx: Context<ResumeStream>) -> Result<()> {
+    let stream = &mut ctx.accounts.stream;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Push the schedule back by the time spent paused so those seconds never vest
+    let paused_duration = stream.effective_pause_duration(current_time);
+    stream.shift_schedule(paused_duration)?;
+    stream.metrics.update_pause(paused_duration);
+
+    stream.status = StreamStatus::Active;
+    stream.paused_at = 0;
+
+    emit!(StreamResumed {
+        stream: stream.key(),
+        resumed_by: ctx.accounts.sender.key(),
+        resumed_at: current_time,
+        paused_duration,
+        total_paused_duration: stream.metrics.total_paused_duration,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamResumed {
+    pub stream: Pubkey,
+    pub resumed_by: Pubkey,
+    pub resumed_at: i64,
+    pub paused_duration: i64,
+    pub total_paused_duration: i64,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct SetEmergencyPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_pause_authority(&authority.key()) @ StreamFlowError::InvalidEmergencyPauseAuthority,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Engage or lift the emergency pause, `exemptions` picks what keeps running while paused
+pub fn set_emergency_pause(ctx: Context<SetEmergencyPause>, paused: bool, exemptions: u8) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if paused {
+        require!(!config.emergency_paused, StreamFlowError::EmergencyPauseActive);
+        config.pause_exemptions = exemptions;
+    } else {
+        require!(config.emergency_paused, StreamFlowError::EmergencyPauseNotActive);
+        config.pause_exemptions = 0;
+    }
+    config.emergency_paused = paused;
+
+    emit!(EmergencyPauseSet {
+        config: config.key(),
+        authority: ctx.accounts.authority.key(),
+        paused,
+        exemptions: config.pause_exemptions,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EmergencyPauseSet {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub exemptions: u8,
+    pub timestamp: i64,
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::*;
+use crate::error::*;
+use crate::metadata::stream_recipient;
+
+/// Lets the holder of a stream fix the token account every withdrawal is paid to
+#[derive(Accounts)]
+pub struct SetPayoutDestination<'info> {
+    #[account(
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = WithdrawSettings::LEN,
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump
+    )]
+    pub withdraw_settings: Account<'info, WithdrawSettings>,
+
+    /// Omit to go back to paying token accounts owned by the holder
+    #[account(
+        constraint = payout_destination.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub payout_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The holder of the stream, `stream.recipient` unless the stream has a position NFT
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    /// The recipient's account holding the position NFT, required for streams created with a position NFT
+    pub recipient_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_payout_destination(ctx: Context<SetPayoutDestination>) -> Result<()> {
+    let stream = &ctx.accounts.stream;
+    let recipient = ctx.accounts.recipient.key();
+
+    require_keys_eq!(
+        stream_recipient(&stream.key(), stream, ctx.accounts.recipient_nft_account.as_ref())?,
+        recipient,
+        StreamFlowError::InvalidRecipient
+    );
+
+    let payout_destination = ctx.accounts.payout_destination.as_ref().map(|account| account.key());
+    let settings = &mut ctx.accounts.withdraw_settings;
+    settings.claim(stream.key(), recipient, recipient, ctx.bumps.withdraw_settings);
+    settings.payout_destination = payout_destination;
+
+    emit!(PayoutDestinationSet {
+        stream: stream.key(),
+        recipient,
+        payout_destination,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PayoutDestinationSet {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub payout_destination: Option<Pubkey>,
+}
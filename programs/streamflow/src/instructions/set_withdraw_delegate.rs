@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::*;
+use crate::error::*;
+use crate::metadata::stream_recipient;
+
+/// Lets the holder of a stream register an account that may withdraw on its behalf
+#[derive(Accounts)]
+pub struct SetWithdrawDelegate<'info> {
+    #[account(
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = WithdrawSettings::LEN,
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump
+    )]
+    pub withdraw_settings: Account<'info, WithdrawSettings>,
+
+    /// The holder of the stream, `stream.recipient` unless the stream has a position NFT
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    /// The recipient's account holding the position NFT, required for streams created with a position NFT
+    pub recipient_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// `None` revokes the current delegate
+pub fn set_withdraw_delegate(ctx: Context<SetWithdrawDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+    let stream = &ctx.accounts.stream;
+    let recipient = ctx.accounts.recipient.key();
+
+    require_keys_eq!(
+        stream_recipient(&stream.key(), stream, ctx.accounts.recipient_nft_account.as_ref())?,
+        recipient,
+        StreamFlowError::InvalidRecipient
+    );
+
+    let settings = &mut ctx.accounts.withdraw_settings;
+    settings.claim(stream.key(), recipient, recipient, ctx.bumps.withdraw_settings);
+    settings.delegate = delegate;
+
+    emit!(WithdrawDelegateSet {
+        stream: stream.key(),
+        recipient,
+        delegate,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct WithdrawDelegateSet {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub delegate: Option<Pubkey>,
+}
@@ -0,0 +1,195 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::token::{amount_after_transfer_fee, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct TopUpStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.sender == sender.key() @ StreamFlowError::InvalidSender,
+        // A rate stream that ran dry and was fully withdrawn is Completed, topping it up reopens it
+        constraint = stream.status == StreamStatus::Active
+            || (stream.status == StreamStatus::Completed && stream.stream_type == StreamType::Rate)
+            @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == sender.key() @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", stream.key().as_ref()],
+        bump = stream.escrow_bump,
+        constraint = escrow_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = stream.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", stream.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn top_up_stream<'info>(
+    ctx: Context<'_, '_, '_, 'info, TopUpStream<'info>>,
+    amount: u64,
+    mode: TopUpMode,
+) -> Result<()> {
+    let stream = &mut ctx.accounts.stream;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Transfer-fee mints withhold part of the deposit, only what reaches the escrow is streamed
+    let deposit_amount = amount;
+    let amount = amount_after_transfer_fee(&ctx.accounts.mint, deposit_amount)?;
+
+    require!(amount > 0, StreamFlowError::ZeroStreamAmount);
+    require!(
+        stream.stream_type == StreamType::Rate || current_time < stream.end_time,
+        StreamFlowError::StreamEnded
+    );
+
+    // The platform fee is charged on top of the added amount, as at creation
+    let fee_amount = FeeConfig::calculate_fee(amount, ctx.accounts.config.fee_config.platform_fee_rate)?;
+    let total_amount = deposit_amount
+        .checked_add(fee_amount)
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+    require!(
+        ctx.accounts.sender_token_account.amount >= total_amount,
+        StreamFlowError::InsufficientTokenBalance
+    );
+
+    let new_amount = stream.amount
+        .checked_add(amount)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    match mode {
+        TopUpMode::ExtendDuration if stream.stream_type == StreamType::Rate => {
+            // A stream that already ran dry resumes from now instead of back-paying the gap
+            let dry_duration = current_time - stream.end_time;
+            if dry_duration > 0 {
+                stream.shift_schedule(dry_duration)?;
+            }
+
+            stream.end_time = Stream::runway_end(stream.start_time, new_amount, stream.rate_per_second)?;
+            stream.status = StreamStatus::Active;
+        },
+        TopUpMode::ExtendDuration => {
+            require!(stream.stream_type == StreamType::Linear, StreamFlowError::InvalidStreamType);
+            // Extending a tranche schedule by raw seconds adds a partial period and
+            // spreads what already vested over more tranches, lowering it
+            require!(
+                current_time <= stream.start_time || stream.release_frequency == WithdrawFrequency::Continuous,
+                StreamFlowError::InvalidStreamType
+            );
+
+            stream.end_time = stream.extended_end_time(amount)?;
+        },
+        TopUpMode::IncreaseRate => {
+            // Rate streams have no fixed end, schedules and milestones pin every tranche amount
+            require!(
+                !matches!(stream.stream_type, StreamType::Rate | StreamType::Schedule | StreamType::Milestone),
+                StreamFlowError::InvalidStreamType
+            );
+
+            if stream.stream_type == StreamType::Linear && current_time > stream.start_time {
+                require!(
+                    stream.release_frequency == WithdrawFrequency::Continuous,
+                    StreamFlowError::InvalidStreamType
+                );
+
+                stream.start_time = stream.increased_rate_start_time(amount, current_time)?;
+            }
+        },
+    }
+
+    stream.amount = new_amount;
+    stream.metrics.total_deposited = stream.metrics.total_deposited
+        .checked_add(amount)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    // Transfer tokens from sender to escrow and the platform fee to the treasury
+    for (destination, transfer_amount) in [
+        (ctx.accounts.escrow_token_account.to_account_info(), deposit_amount),
+        (ctx.accounts.fee_vault.to_account_info(), fee_amount),
+    ] {
+        if transfer_amount == 0 {
+            continue;
+        }
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.sender_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            destination,
+            ctx.accounts.sender.to_account_info(),
+            ctx.remaining_accounts,
+            transfer_amount,
+            &[],
+        )?;
+    }
+
+    if fee_amount > 0 {
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, fee_amount)?)?;
+    }
+
+    emit!(StreamToppedUp {
+        stream: stream.key(),
+        sender: ctx.accounts.sender.key(),
+        amount,
+        fee_amount,
+        mode,
+        new_amount: stream.amount,
+        start_time: stream.start_time,
+        end_time: stream.end_time,
+        total_deposited: stream.metrics.total_deposited,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamToppedUp {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub fee_amount: u64,
+    pub mode: TopUpMode,
+    pub new_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub total_deposited: u64,
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+/// Permissionless: anyone can unlock a milestone once the oracle proves its price condition
+#[derive(Accounts)]
+pub struct UnlockPriceMilestone<'info> {
+    #[account(
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
+        bump = stream.bump,
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = stream.status == StreamStatus::Active @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+        constraint = milestone_schedule.approver == price_condition.key() @ StreamFlowError::InvalidMilestoneProof,
+    )]
+    pub milestone_schedule: Account<'info, MilestoneSchedule>,
+
+    #[account(
+        seeds = [b"price_condition", stream.key().as_ref()],
+        bump = price_condition.bump,
+        constraint = price_condition.price_feed == price_feed.key() @ StreamFlowError::OraclePriceFeedError,
+    )]
+    pub price_condition: Account<'info, PriceCondition>,
+
+    /// CHECK: Key is pinned by `price_condition`, the data is parsed by `OraclePrice::load`
+    pub price_feed: AccountInfo<'info>,
+}
+
+pub fn unlock_price_milestone(ctx: Context<UnlockPriceMilestone>, index: u8) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let price_condition = &ctx.accounts.price_condition;
+
+    let oracle_price = OraclePrice::load(
+        &ctx.accounts.price_feed,
+        current_time,
+        price_condition.max_staleness,
+    )?;
+    require!(oracle_price.expo == price_condition.expo, StreamFlowError::OraclePriceFeedError);
+
+    let threshold = *price_condition
+        .thresholds
+        .get(index as usize)
+        .ok_or(StreamFlowError::InvalidMilestoneParameters)?;
+    require!(oracle_price.price > threshold, StreamFlowError::PriceConditionNotMet);
+
+    let amount = ctx.accounts.milestone_schedule.approve(index, current_time)?;
+
+    emit!(PriceMilestoneUnlocked {
+        stream: ctx.accounts.stream.key(),
+        price_feed: price_condition.price_feed,
+        index,
+        amount,
+        price: oracle_price.price,
+        threshold,
+        unlocked_at: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PriceMilestoneUnlocked {
+    pub stream: Pubkey,
+    pub price_feed: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+    pub price: i64,
+    pub threshold: i64,
+    pub unlocked_at: i64,
+}
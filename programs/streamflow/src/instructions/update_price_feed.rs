@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(
+        mut,
+        has_one = authority @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.price = price;
+    price_feed.publish_time = current_time;
+
+    emit!(PriceFeedUpdated {
+        price_feed: price_feed.key(),
+        price,
+        expo: price_feed.expo,
+        publish_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PriceFeedUpdated {
+    pub price_feed: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
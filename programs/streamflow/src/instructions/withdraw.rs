@@ -1,188 +1,245 @@
-```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
-use crate::errors::*;
+use crate::error::*;
+use crate::calculate_available_amount;
+use crate::metadata::stream_recipient;
+use crate::token::{amount_after_transfer_fee, transfer_tokens, unwrap_sol};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(
         mut,
-        seeds = [b"stream", stream.sender.as_ref(), stream.recipient.as_ref(), &stream.seed.to_le_bytes()],
+        seeds = [b"stream", stream.sender.as_ref(), stream.stream_id.as_bytes()],
         bump = stream.bump,
-        constraint = stream.recipient == recipient.key() @ StreamFlowError::UnauthorizedRecipient,
-        constraint = !stream.is_cancelled @ StreamFlowError::StreamCancelled,
-        constraint = stream.withdrawn_amount < stream.total_amount @ StreamFlowError::StreamFullyWithdrawn
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch,
+        constraint = matches!(stream.status, StreamStatus::Active | StreamStatus::Paused) @ StreamFlowError::StreamNotActive,
+        constraint = stream.withdrawn_amount < stream.amount @ StreamFlowError::StreamFullyWithdrawn
     )]
     pub stream: Account<'info, Stream>,
 
     #[account(
         mut,
-        constraint = stream_token_account.mint == stream.mint @ StreamFlowError::InvalidMint,
+        constraint = stream_token_account.key() == stream.escrow_token_account @ StreamFlowError::InvalidEscrowAccount,
+        constraint = stream_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
         constraint = stream_token_account.owner == stream.key() @ StreamFlowError::InvalidTokenAccount
     )]
-    pub stream_token_account: Account<'info, TokenAccount>,
+    pub stream_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// Owned by the holder, or the registered payout destination when there is one.
+    /// Omit to receive a wrapped SOL stream as native SOL through `unwrap_account`
     #[account(
         mut,
-        constraint = recipient_token_account.mint == stream.mint @ StreamFlowError::InvalidMint,
-        constraint = recipient_token_account.owner == recipient.key() @ StreamFlowError::InvalidTokenAccount
+        constraint = recipient_token_account.mint == stream.mint @ StreamFlowError::InvalidTokenMint,
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
+    /// CHECK: Temporary wrapped SOL account, created and closed within the instruction
+    #[account(
+        mut,
+        seeds = [b"unwrap", stream.key().as_ref()],
+        bump,
+    )]
+    pub unwrap_account: Option<AccountInfo<'info>>,
+
+    #[account(address = stream.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The holder of the stream or its registered withdraw delegate, anyone if `stream.withdrawal_public`
     #[account(mut)]
-    pub recipient: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// The recipient's account holding the position NFT, required for streams created with a position NFT
+    pub recipient_nft_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The stream's `WithdrawSettings`, which may not exist, read by `WithdrawSettings::load`
+    #[account(
+        seeds = [b"withdraw_settings", stream.key().as_ref()],
+        bump,
+    )]
+    pub withdraw_settings: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// Required for `StreamType::Schedule` streams
+    #[account(
+        seeds = [b"vesting_schedule", stream.key().as_ref()],
+        bump = vesting_schedule.bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+
+    /// Required for `StreamType::Milestone` streams
+    #[account(
+        seeds = [b"milestone_schedule", stream.key().as_ref()],
+        bump = milestone_schedule.bump,
+    )]
+    pub milestone_schedule: Option<Account<'info, MilestoneSchedule>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.allows(Config::PAUSE_EXEMPT_WITHDRAW) @ StreamFlowError::EmergencyPauseActive,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", stream.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn withdraw(ctx: Context<Withdraw>, amount: Option<u64>) -> Result<()> {
+pub fn withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+    amount: Option<u64>,
+) -> Result<()> {
     let stream = &mut ctx.accounts.stream;
-    let current_time = Clock::get()?.unix_timestamp as u64;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let recipient = stream_recipient(&stream.key(), stream, ctx.accounts.recipient_nft_account.as_ref())?;
+    let authority = ctx.accounts.authority.key();
+
+    // Settings registered by a previous holder no longer apply
+    let settings = WithdrawSettings::load(&ctx.accounts.withdraw_settings, &recipient)?;
+    let is_delegate = settings.as_ref().and_then(|settings| settings.delegate) == Some(authority);
+    require!(
+        authority == recipient || is_delegate || stream.withdrawal_public,
+        StreamFlowError::OnlyRecipientCanWithdraw
+    );
+
+    match ctx.accounts.recipient_token_account.as_ref() {
+        Some(recipient_token_account) => {
+            WithdrawSettings::check_payout_account(settings.as_ref(), recipient_token_account, &recipient)?
+        },
+        // Native SOL goes to the signer, so only the holder itself may take it
+        None => require!(
+            authority == recipient && settings.and_then(|settings| settings.payout_destination).is_none(),
+            StreamFlowError::InvalidTokenAccount
+        ),
+    }
+    require!(current_time >= stream.start_time, StreamFlowError::StreamNotStarted);
 
     // Calculate available amount to withdraw
-    let available_amount = calculate_available_amount(stream, current_time)?;
-    
-    require!(available_amount > 0, StreamFlowError::NoTokensAvailable);
+    let available_amount = calculate_available_amount(
+        stream,
+        ctx.accounts.vesting_schedule.as_deref(),
+        ctx.accounts.milestone_schedule.as_deref(),
+        current_time,
+    )?;
+
+    // Nothing to withdraw from a milestone stream means no new tranche was approved
+    require!(
+        available_amount > 0 || stream.stream_type != StreamType::Milestone,
+        StreamFlowError::MilestoneNotReached
+    );
+    require!(available_amount > 0, StreamFlowError::InsufficientWithdrawableAmount);
 
-    // Determine withdrawal amount
+    // Determine withdrawal amount, `None` withdraws everything available
     let withdraw_amount = if let Some(requested_amount) = amount {
-        require!(requested_amount <= available_amount, StreamFlowError::InsufficientAvailableTokens);
+        require!(
+            requested_amount > 0 && requested_amount <= available_amount,
+            StreamFlowError::InvalidWithdrawalAmount
+        );
         requested_amount
     } else {
         available_amount
     };
 
+    // The withdrawal fee comes out of the withdrawn amount
+    let fee_amount = FeeConfig::calculate_fee(withdraw_amount, ctx.accounts.config.fee_config.withdrawal_fee_rate)?;
+    let recipient_amount = withdraw_amount
+        .checked_sub(fee_amount)
+        .ok_or(StreamFlowError::FeeCalculationError)?;
+
     // Update stream state
-    stream.withdrawn_amount = stream.withdrawn_amount
-        .checked_add(withdraw_amount)
-        .ok_or(StreamFlowError::MathOverflow)?;
-    
-    stream.last_withdrawn_at = current_time;
+    stream.update_after_withdrawal(withdraw_amount, current_time)?;
+
+    if stream.is_fully_withdrawn() {
+        stream.status = StreamStatus::Completed;
+    }
 
     // Transfer tokens from stream account to recipient
     let seeds = &[
         b"stream",
         stream.sender.as_ref(),
-        stream.recipient.as_ref(),
-        &stream.seed.to_le_bytes(),
+        stream.stream_id.as_bytes(),
         &[stream.bump],
     ];
     let signer_seeds = &[&seeds[..]];
 
-    let transfer_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.stream_token_account.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: stream.to_account_info(),
+    match ctx.accounts.recipient_token_account.as_ref() {
+        Some(recipient_token_account) => transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.stream_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            recipient_token_account.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            recipient_amount,
+            signer_seeds,
+        )?,
+        None => {
+            let unwrap_account = ctx.accounts.unwrap_account.as_ref()
+                .ok_or(StreamFlowError::InvalidTokenAccount)?;
+            let stream_key = stream.key();
+            let unwrap_seeds = &[b"unwrap", stream_key.as_ref(), &[ctx.bumps.unwrap_account]];
+
+            unwrap_sol(
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                ctx.accounts.stream_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                stream.to_account_info(),
+                signer_seeds,
+                unwrap_account.to_account_info(),
+                unwrap_seeds,
+                ctx.accounts.authority.to_account_info(),
+                &[(ctx.accounts.authority.to_account_info(), recipient_amount)],
+            )?;
         },
-        signer_seeds,
-    );
+    }
 
-    token::transfer(transfer_ctx, withdraw_amount)?;
+    if fee_amount > 0 {
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            ctx.accounts.stream_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.fee_vault.to_account_info(),
+            stream.to_account_info(),
+            ctx.remaining_accounts,
+            fee_amount,
+            signer_seeds,
+        )?;
+        ctx.accounts.treasury.record_collection(amount_after_transfer_fee(&ctx.accounts.mint, fee_amount)?)?;
+    }
 
     // Emit withdrawal event
     emit!(WithdrawEvent {
         stream: stream.key(),
-        recipient: ctx.accounts.recipient.key(),
+        recipient,
         amount: withdraw_amount,
+        fee_amount,
         timestamp: current_time,
-        remaining_amount: stream.total_amount
-            .checked_sub(stream.withdrawn_amount)
-            .ok_or(StreamFlowError::MathOverflow)?,
+        remaining_amount: stream.remaining_amount(),
     });
 
     Ok(())
 }
 
-fn calculate_available_amount(stream: &Stream, current_time: u64) -> Result<u64> {
-    // If stream hasn't started yet
-    if current_time < stream.start_time {
-        return Ok(0);
-    }
-
-    // If stream has ended, all remaining tokens are available
-    if current_time >= stream.end_time {
-        return Ok(stream.total_amount
-            .checked_sub(stream.withdrawn_amount)
-            .ok_or(StreamFlowError::MathOverflow)?);
-    }
-
-    // Calculate vested amount based on time elapsed
-    let time_elapsed = current_time
-        .checked_sub(stream.start_time)
-        .ok_or(StreamFlowError::MathOverflow)?;
-    
-    let total_duration = stream.end_time
-        .checked_sub(stream.start_time)
-        .ok_or(StreamFlowError::MathOverflow)?;
-
-    // Handle cliff period
-    if let Some(cliff_time) = stream.cliff_time {
-        if current_time < cliff_time {
-            return Ok(0);
-        }
-    }
-
-    let vested_amount = match stream.stream_type {
-        StreamType::Linear => {
-            // Linear vesting: amount = total_amount * time_elapsed / total_duration
-            (stream.total_amount as u128)
-                .checked_mul(time_elapsed as u128)
-                .and_then(|result| result.checked_div(total_duration as u128))
-                .and_then(|result| u64::try_from(result).ok())
-                .ok_or(StreamFlowError::MathOverflow)?
-        },
-        StreamType::Cliff => {
-            // Cliff vesting: all tokens available after cliff period
-            if let Some(cliff_time) = stream.cliff_time {
-                if current_time >= cliff_time {
-                    stream.total_amount
-                } else {
-                    0
-                }
-            } else {
-                return Err(StreamFlowError::InvalidStreamType.into());
-            }
-        },
-        StreamType::SteppedLinear => {
-            // Stepped linear vesting with release frequency
-            let steps_elapsed = time_elapsed
-                .checked_div(stream.release_frequency)
-                .ok_or(StreamFlowError::MathOverflow)?;
-            
-            let total_steps = total_duration
-                .checked_div(stream.release_frequency)
-                .ok_or(StreamFlowError::MathOverflow)?;
-
-            if total_steps == 0 {
-                return Err(StreamFlowError::InvalidStreamConfiguration.into());
-            }
-
-            (stream.total_amount as u128)
-                .checked_mul(steps_elapsed as u128)
-                .and_then(|result| result.checked_div(total_steps as u128))
-                .and_then(|result| u64::try_from(result).ok())
-                .ok_or(StreamFlowError::MathOverflow)?
-        },
-    };
-
-    // Available amount is vested amount minus already withdrawn amount
-    let available = vested_amount
-        .checked_sub(stream.withdrawn_amount)
-        .ok_or(StreamFlowError::MathOverflow)?;
-
-    Ok(available)
-}
-
 #[event]
 pub struct WithdrawEvent {
     pub stream: Pubkey,
     pub recipient: Pubkey,
     pub amount: u64,
-    pub timestamp: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
     pub remaining_amount: u64,
 }
-```
\ No newline at end of file
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::error::*;
+use crate::token::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ StreamFlowError::AuthorizationFailed,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.mint.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = treasury.vault @ StreamFlowError::InvalidTokenAccount,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == treasury.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = destination_token_account.owner == config.fee_config.fee_recipient @ StreamFlowError::InvalidFeeRecipient,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = treasury.mint @ StreamFlowError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn withdraw_treasury<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawTreasury<'info>>,
+    amount: u64,
+) -> Result<()> {
+    sweep_treasury(
+        &mut ctx.accounts.treasury,
+        &ctx.accounts.fee_vault,
+        &ctx.accounts.destination_token_account,
+        &ctx.accounts.mint,
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+        amount,
+    )
+}
+
+/// Move `amount` of collected fees from `fee_vault` to `destination_token_account`.
+///
+/// The caller has checked that the accounts belong to `treasury` and that the
+/// destination is owned by the fee recipient.
+pub(crate) fn sweep_treasury<'info>(
+    treasury: &mut Account<'info, Treasury>,
+    fee_vault: &InterfaceAccount<'info, TokenAccount>,
+    destination_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, StreamFlowError::InvalidWithdrawalAmount);
+    require!(
+        fee_vault.amount >= amount,
+        StreamFlowError::InsufficientTokenBalance
+    );
+
+    treasury.record_withdrawal(amount)?;
+
+    let seeds = &[
+        b"treasury",
+        treasury.mint.as_ref(),
+        &[treasury.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    transfer_tokens(
+        token_program,
+        fee_vault.to_account_info(),
+        mint,
+        destination_token_account.to_account_info(),
+        treasury.to_account_info(),
+        remaining_accounts,
+        amount,
+        signer_seeds,
+    )?;
+
+    emit!(TreasuryWithdrawn {
+        treasury: treasury.key(),
+        mint: treasury.mint,
+        destination: destination_token_account.key(),
+        amount,
+        total_collected: treasury.total_collected,
+        total_withdrawn: treasury.total_withdrawn,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_collected: u64,
+    pub total_withdrawn: u64,
+}
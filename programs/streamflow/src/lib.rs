@@ -1,202 +1,269 @@
-```rust
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
-use std::mem::size_of;
+use anchor_lang::system_program;
+
+pub mod error;
+pub mod instructions;
+pub mod metadata;
+pub mod state;
+pub mod token;
+
+use error::StreamFlowError;
+use instructions::*;
+use state::*;
 
-declare_id!("SF1ow1234567890123456789012345678901234567");
+declare_id!("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
 
 #[program]
 pub mod streamflow {
     use super::*;
 
-    pub fn initialize_stream(
-        ctx: Context<InitializeStream>,
-        recipient: Pubkey,
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        pause_guardian: Pubkey,
+        fee_config: FeeConfig,
+        timelock_delay: i64,
+    ) -> Result<()> {
+        instructions::initialize_config::initialize_config(ctx, pause_guardian, fee_config, timelock_delay)
+    }
+
+    pub fn queue_config_change(ctx: Context<QueueConfigChange>, change: ConfigChange) -> Result<()> {
+        instructions::queue_config_change::queue_config_change(ctx, change)
+    }
+
+    pub fn execute_config_change<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteConfigChange<'info>>) -> Result<()> {
+        instructions::execute_config_change::execute_config_change(ctx)
+    }
+
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        instructions::cancel_config_change::cancel_config_change(ctx)
+    }
+
+    pub fn initialize_governance(
+        ctx: Context<InitializeGovernance>,
+        quorum_votes: u64,
+        voting_period: i64,
+    ) -> Result<()> {
+        instructions::initialize_governance::initialize_governance(ctx, quorum_votes, voting_period)
+    }
+
+    pub fn create_governance_proposal(ctx: Context<CreateGovernanceProposal>, change: ConfigChange) -> Result<()> {
+        instructions::create_governance_proposal::create_governance_proposal(ctx, change)
+    }
+
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+        approve: bool,
+        votes: u64,
+    ) -> Result<()> {
+        instructions::cast_vote::cast_vote(ctx, approve, votes)
+    }
+
+    pub fn relinquish_vote<'info>(ctx: Context<'_, '_, '_, 'info, RelinquishVote<'info>>) -> Result<()> {
+        instructions::relinquish_vote::relinquish_vote(ctx)
+    }
+
+    pub fn queue_governance_proposal(ctx: Context<QueueGovernanceProposal>) -> Result<()> {
+        instructions::queue_governance_proposal::queue_governance_proposal(ctx)
+    }
+
+    pub fn set_emergency_pause(ctx: Context<SetEmergencyPause>, paused: bool, exemptions: u8) -> Result<()> {
+        instructions::set_emergency_pause::set_emergency_pause(ctx, paused, exemptions)
+    }
+
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+        instructions::initialize_treasury::initialize_treasury(ctx)
+    }
+
+    pub fn withdraw_treasury<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawTreasury<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_treasury::withdraw_treasury(ctx, amount)
+    }
+
+    pub fn initialize_mint_registry(
+        ctx: Context<InitializeMintRegistry>,
+        mode: MintListMode,
+        max_decimals: u8,
+        reject_freezable: bool,
+    ) -> Result<()> {
+        instructions::initialize_mint_registry::initialize_mint_registry(ctx, mode, max_decimals, reject_freezable)
+    }
+
+    pub fn initialize_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStream<'info>>,
+        stream_id: String,
+        amount: u64,
         start_time: i64,
         end_time: i64,
-        amount: u64,
         cliff_time: Option<i64>,
+        stream_type: StreamType,
+        release_frequency: WithdrawFrequency,
+        rate_per_second: u64,
+        initial_unlock: UnlockAmount,
+        cliff_unlock: UnlockAmount,
         cancelable_by_sender: bool,
         cancelable_by_recipient: bool,
         transferable_by_sender: bool,
         transferable_by_recipient: bool,
+        can_pause: bool,
+        automatic_withdrawal: bool,
+        withdrawal_public: bool,
+        schedule: Option<StreamSchedule>,
+        position_nft: Option<PositionNft>,
     ) -> Result<()> {
-        require!(start_time < end_time, StreamError::InvalidTimeRange);
-        require!(amount > 0, StreamError::InvalidAmount);
-        
-        if let Some(cliff) = cliff_time {
-            require!(cliff >= start_time && cliff <= end_time, StreamError::InvalidCliffTime);
-        }
+        instructions::create_stream::handler(
+            ctx,
+            StreamParams {
+                stream_id,
+                amount,
+                start_time,
+                end_time,
+                cliff_time,
+                stream_type,
+                release_frequency,
+                rate_per_second,
+                initial_unlock,
+                cliff_unlock,
+                cancelable_by_sender,
+                cancelable_by_recipient,
+                transferable_by_sender,
+                transferable_by_recipient,
+                can_pause,
+                automatic_withdrawal,
+                withdrawal_public,
+            },
+            schedule,
+            position_nft,
+        )
+    }
 
-        let stream = &mut ctx.accounts.stream;
-        let clock = Clock::get()?;
-
-        stream.sender = ctx.accounts.sender.key();
-        stream.recipient = recipient;
-        stream.mint = ctx.accounts.mint.key();
-        stream.escrow_token_account = ctx.accounts.escrow_token_account.key();
-        stream.start_time = start_time;
-        stream.end_time = end_time;
-        stream.cliff_time = cliff_time;
-        stream.amount = amount;
-        stream.withdrawn_amount = 0;
-        stream.canceled_at = None;
-        stream.cancelable_by_sender = cancelable_by_sender;
-        stream.cancelable_by_recipient = cancelable_by_recipient;
-        stream.transferable_by_sender = transferable_by_sender;
-        stream.transferable_by_recipient = transferable_by_recipient;
-        stream.created_at = clock.unix_timestamp;
-        stream.bump = *ctx.bumps.get("stream").unwrap();
-
-        // Transfer tokens to escrow
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.sender_token_account.to_account_info(),
-            to: ctx.accounts.escrow_token_account.to_account_info(),
-            authority: ctx.accounts.sender.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        emit!(StreamCreated {
-            stream: stream.key(),
-            sender: stream.sender,
-            recipient: stream.recipient,
-            mint: stream.mint,
-            amount: stream.amount,
-            start_time: stream.start_time,
-            end_time: stream.end_time,
-        });
+    pub fn create_stream_template(
+        ctx: Context<CreateStreamTemplate>,
+        template_id: String,
+        config: StreamConfig,
+    ) -> Result<()> {
+        instructions::create_stream_template::create_stream_template(ctx, template_id, config)
+    }
 
-        Ok(())
+    pub fn create_stream_from_template<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStreamFromTemplate<'info>>,
+        stream_id: String,
+        amount: u64,
+        start_time: i64,
+    ) -> Result<()> {
+        instructions::create_stream_from_template::create_stream_from_template(ctx, stream_id, amount, start_time)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
-        let clock = Clock::get()?;
-        let current_time = clock.unix_timestamp;
-
-        require!(stream.canceled_at.is_none(), StreamError::StreamCanceled);
-        require!(current_time >= stream.start_time, StreamError::StreamNotStarted);
-
-        let available_amount = calculate_available_amount(stream, current_time)?;
-        require!(amount <= available_amount, StreamError::InsufficientFunds);
-
-        stream.withdrawn_amount = stream.withdrawn_amount.checked_add(amount).unwrap();
-
-        // Transfer tokens from escrow to recipient
-        let seeds = &[
-            b"stream",
-            stream.sender.as_ref(),
-            stream.recipient.as_ref(),
-            stream.mint.as_ref(),
-            &stream.start_time.to_le_bytes(),
-            &[stream.bump],
-        ];
-        let signer = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.stream.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, amount)?;
-
-        emit!(TokensWithdrawn {
-            stream: stream.key(),
-            recipient: ctx.accounts.recipient.key(),
-            amount,
-            withdrawn_amount: stream.withdrawn_amount,
-        });
+    pub fn create_streams_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStreamsBatch<'info>>,
+        entries: Vec<BatchStreamEntry>,
+    ) -> Result<()> {
+        instructions::create_streams_batch::create_streams_batch(ctx, entries)
+    }
 
-        Ok(())
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+        amount: Option<u64>,
+    ) -> Result<()> {
+        instructions::withdraw::withdraw(ctx, amount)
     }
 
-    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
-        let clock = Clock::get()?;
-        let current_time = clock.unix_timestamp;
+    pub fn set_withdraw_delegate(ctx: Context<SetWithdrawDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+        instructions::set_withdraw_delegate::set_withdraw_delegate(ctx, delegate)
+    }
 
-        require!(stream.canceled_at.is_none(), StreamError::StreamAlreadyCanceled);
+    pub fn set_payout_destination(ctx: Context<SetPayoutDestination>) -> Result<()> {
+        instructions::set_payout_destination::set_payout_destination(ctx)
+    }
 
-        let is_sender = ctx.accounts.authority.key() == stream.sender;
-        let is_recipient = ctx.accounts.authority.key() == stream.recipient;
+    pub fn crank_withdraw<'info>(ctx: Context<'_, '_, '_, 'info, CrankWithdraw<'info>>) -> Result<()> {
+        instructions::crank_withdraw::crank_withdraw(ctx)
+    }
 
-        require!(
-            (is_sender && stream.cancelable_by_sender) || 
-            (is_recipient && stream.cancelable_by_recipient),
-            StreamError::UnauthorizedCancel
-        );
+    pub fn cancel_stream<'info>(ctx: Context<'_, '_, '_, 'info, CancelStream<'info>>) -> Result<()> {
+        instructions::cancel_stream::cancel_stream(ctx)
+    }
 
-        stream.canceled_at = Some(current_time);
-
-        let available_amount = calculate_available_amount(stream, current_time)?;
-        let remaining_amount = stream.amount.checked_sub(stream.withdrawn_amount).unwrap();
-
-        // Transfer available tokens to recipient if any
-        if available_amount > 0 {
-            let seeds = &[
-                b"stream",
-                stream.sender.as_ref(),
-                stream.recipient.as_ref(),
-                stream.mint.as_ref(),
-                &stream.start_time.to_le_bytes(),
-                &[stream.bump],
-            ];
-            let signer = &[&seeds[..]];
-
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.recipient_token_account.to_account_info(),
-                authority: ctx.accounts.stream.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, available_amount)?;
-
-            stream.withdrawn_amount = stream.withdrawn_amount.checked_add(available_amount).unwrap();
-        }
+    pub fn close_stream<'info>(ctx: Context<'_, '_, '_, 'info, CloseStream<'info>>) -> Result<()> {
+        instructions::close_stream::close_stream(ctx)
+    }
 
-        // Return remaining tokens to sender
-        let remaining_to_sender = remaining_amount.checked_sub(available_amount).unwrap();
-        if remaining_to_sender > 0 {
-            let seeds = &[
-                b"stream",
-                stream.sender.as_ref(),
-                stream.recipient.as_ref(),
-                stream.mint.as_ref(),
-                &stream.start_time.to_le_bytes(),
-                &[stream.bump],
-            ];
-            let signer = &[&seeds[..]];
-
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.sender_token_account.to_account_info(),
-                authority: ctx.accounts.stream.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, remaining_to_sender)?;
-        }
+    pub fn migrate_stream(ctx: Context<MigrateStream>) -> Result<()> {
+        instructions::migrate_stream::migrate_stream(ctx)
+    }
 
-        emit!(StreamCanceled {
-            stream: stream.key(),
-            canceled_by: ctx.accounts.authority.key(),
-            canceled_at: current_time,
-            recipient_amount: available_amount,
-            sender_amount: remaining_to_sender,
-        });
+    pub fn approve_milestone(ctx: Context<ApproveMilestone>, index: u8) -> Result<()> {
+        instructions::approve_milestone::approve_milestone(ctx, index)
+    }
 
-        Ok(())
+    pub fn initialize_price_feed(ctx: Context<InitializePriceFeed>, expo: i32) -> Result<()> {
+        instructions::initialize_price_feed::initialize_price_feed(ctx, expo)
+    }
+
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64) -> Result<()> {
+        instructions::update_price_feed::update_price_feed(ctx, price)
+    }
+
+    pub fn create_price_condition(
+        ctx: Context<CreatePriceCondition>,
+        expo: i32,
+        max_staleness: i64,
+        thresholds: Vec<i64>,
+    ) -> Result<()> {
+        instructions::create_price_condition::create_price_condition(ctx, expo, max_staleness, thresholds)
+    }
+
+    pub fn unlock_price_milestone(ctx: Context<UnlockPriceMilestone>, index: u8) -> Result<()> {
+        instructions::unlock_price_milestone::unlock_price_milestone(ctx, index)
+    }
+
+    pub fn pause_stream(ctx: Context<PauseStream>) -> Result<()> {
+        instructions::pause_stream::pause_stream(ctx)
+    }
+
+    pub fn resume_stream(ctx: Context<ResumeStream>) -> Result<()> {
+        instructions::resume_stream::resume_stream(ctx)
+    }
+
+    pub fn top_up_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, TopUpStream<'info>>,
+        amount: u64,
+        mode: TopUpMode,
+    ) -> Result<()> {
+        instructions::top_up_stream::top_up_stream(ctx, amount, mode)
+    }
+
+    pub fn create_multisig(ctx: Context<CreateMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        instructions::create_multisig::create_multisig(ctx, signers, threshold)
+    }
+
+    pub fn propose_multisig_action(
+        ctx: Context<ProposeMultisigAction>,
+        stream: Pubkey,
+        action: MultisigAction,
+    ) -> Result<()> {
+        instructions::propose_multisig_action::propose_multisig_action(ctx, stream, action)
+    }
+
+    pub fn approve_multisig_action(ctx: Context<ApproveMultisigAction>) -> Result<()> {
+        instructions::approve_multisig_action::approve_multisig_action(ctx)
+    }
+
+    pub fn execute_multisig_action<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteMultisigAction<'info>>,
+    ) -> Result<()> {
+        instructions::execute_multisig_action::execute_multisig_action(ctx)
     }
 
     pub fn transfer_stream(ctx: Context<TransferStream>, new_recipient: Pubkey) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
 
-        require!(stream.canceled_at.is_none(), StreamError::StreamCanceled);
+        require!(stream.status != StreamStatus::Cancelled, StreamFlowError::StreamAlreadyCancelled);
+        // Streams with a position NFT move with the NFT instead
+        require!(!stream.nft_recipient, StreamFlowError::StreamNotTransferable);
 
         let is_sender = ctx.accounts.authority.key() == stream.sender;
         let is_recipient = ctx.accounts.authority.key() == stream.recipient;
@@ -204,7 +271,7 @@ pub mod streamflow {
         require!(
             (is_sender && stream.transferable_by_sender) || 
             (is_recipient && stream.transferable_by_recipient),
-            StreamError::UnauthorizedTransfer
+            StreamFlowError::StreamNotTransferable
         );
 
         let old_recipient = stream.recipient;
@@ -229,8 +296,8 @@ pub mod streamflow {
     ) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
 
-        require!(ctx.accounts.authority.key() == stream.sender, StreamError::UnauthorizedUpdate);
-        require!(stream.canceled_at.is_none(), StreamError::StreamCanceled);
+        require!(ctx.accounts.authority.key() == stream.sender, StreamFlowError::Unauthorized);
+        require!(stream.status != StreamStatus::Cancelled, StreamFlowError::StreamAlreadyCancelled);
 
         if let Some(cancelable_sender) = cancelable_by_sender {
             stream.cancelable_by_sender = cancelable_sender;
@@ -254,168 +321,315 @@ pub mod streamflow {
     }
 }
 
-fn calculate_available_amount(stream: &Stream, current_time: i64) -> Result<u64> {
-    if let Some(canceled_at) = stream.canceled_at {
-        let effective_time = std::cmp::min(canceled_at, current_time);
-        return calculate_streamed_amount(stream, effective_time);
-    }
+pub(crate) fn calculate_available_amount(
+    stream: &Stream,
+    vesting_schedule: Option<&VestingSchedule>,
+    milestone_schedule: Option<&MilestoneSchedule>,
+    current_time: i64,
+) -> Result<u64> {
+    let current_time = stream.accrual_cutoff(current_time);
+
+    let streamed_amount = calculate_streamed_amount(stream, vesting_schedule, milestone_schedule, current_time)?;
+    Ok(streamed_amount.saturating_sub(stream.withdrawn_amount))
+}
 
+pub(crate) fn calculate_streamed_amount(
+    stream: &Stream,
+    vesting_schedule: Option<&VestingSchedule>,
+    milestone_schedule: Option<&MilestoneSchedule>,
+    current_time: i64,
+) -> Result<u64> {
     if current_time < stream.start_time {
         return Ok(0);
     }
 
+    // Milestones are released by approval, `end_time` alone never unlocks them
+    if current_time >= stream.end_time && stream.stream_type != StreamType::Milestone {
+        return Ok(stream.amount);
+    }
+
+    // The initial unlock is released at `start_time`, the cliff unlock and
+    // everything accrued on the remainder so far is released at the cliff
+    let mut unlocked_amount = stream.initial_unlock_amount;
+
     if let Some(cliff_time) = stream.cliff_time {
         if current_time < cliff_time {
-            return Ok(0);
+            return Ok(unlocked_amount);
         }
     }
 
-    let streamed_amount = calculate_streamed_amount(stream, current_time)?;
-    Ok(streamed_amount.checked_sub(stream.withdrawn_amount).unwrap())
+    unlocked_amount = unlocked_amount
+        .checked_add(stream.cliff_unlock_amount)
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+    let vested_amount = calculate_vested_remainder(stream, vesting_schedule, milestone_schedule, current_time)?;
+
+    Ok(unlocked_amount
+        .checked_add(vested_amount)
+        .ok_or(StreamFlowError::MathematicalOverflow)?)
 }
 
-fn calculate_streamed_amount(stream: &Stream, current_time: i64) -> Result<u64> {
-    if current_time <= stream.start_time {
-        return Ok(0);
+/// How much of `Stream::vesting_amount` the stream type has released by `current_time`
+fn calculate_vested_remainder(
+    stream: &Stream,
+    vesting_schedule: Option<&VestingSchedule>,
+    milestone_schedule: Option<&MilestoneSchedule>,
+    current_time: i64,
+) -> Result<u64> {
+    let vesting_amount = stream.vesting_amount();
+
+    match stream.stream_type {
+        StreamType::Linear => {
+            // Continuous streams count seconds, periodic ones count unlocked tranches
+            let elapsed_periods = stream.release_frequency
+                .periods_elapsed(stream.start_time, current_time)
+                .ok_or(StreamFlowError::MathematicalOverflow)?;
+            let total_periods = stream.release_frequency
+                .period_count(stream.start_time, stream.end_time)
+                .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+            let streamed_amount = (vesting_amount as u128)
+                .checked_mul(elapsed_periods as u128)
+                .and_then(|result| result.checked_div(total_periods as u128))
+                .and_then(|result| u64::try_from(result).ok())
+                .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+            Ok(streamed_amount)
+        },
+        StreamType::Rate => {
+            let elapsed_time = current_time
+                .checked_sub(stream.start_time)
+                .ok_or(StreamFlowError::MathematicalUnderflow)? as u128;
+
+            let streamed_amount = elapsed_time
+                .checked_mul(stream.rate_per_second as u128)
+                .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+            Ok(std::cmp::min(streamed_amount, vesting_amount as u128) as u64)
+        },
+        // Everything unlocks at the cliff, which the caller has already checked
+        StreamType::Cliff => Ok(vesting_amount),
+        // Nothing unlocks until `end_time`
+        StreamType::Unlock => Ok(0),
+        StreamType::Schedule => {
+            let vesting_schedule = vesting_schedule.ok_or(StreamFlowError::VestingScheduleNotFound)?;
+
+            // Checkpoints keep their original timestamps, so undo any pause shift
+            let schedule_time = current_time
+                .checked_sub(stream.metrics.total_paused_duration)
+                .ok_or(StreamFlowError::MathematicalUnderflow)?;
+
+            vesting_schedule.vested_amount(schedule_time)
+        },
+        StreamType::Milestone => {
+            let milestone_schedule = milestone_schedule.ok_or(StreamFlowError::MilestoneScheduleNotFound)?;
+            milestone_schedule.approved_amount(current_time)
+        },
     }
+}
 
-    if current_time >= stream.end_time {
-        return Ok(stream.amount);
+/// Create the PDA `account` owned by `owner`, the way Anchor's `init` does.
+///
+/// An address that already holds lamports cannot go through `create_account`,
+/// so it is topped up to rent exemption, allocated and assigned instead.
+pub(crate) fn create_pda_account<'info>(
+    system_program: &Program<'info, System>,
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    require!(
+        account.owner == &system_program::ID && account.data_is_empty(),
+        StreamFlowError::InvalidAccountState
+    );
+
+    let signer_seeds = &[seeds];
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount { from: payer, to: account },
+                signer_seeds,
+            ),
+            rent_exempt_lamports,
+            space as u64,
+            owner,
+        );
     }
 
-    let elapsed_time = current_time.checked_sub(stream.start_time).unwrap() as u64;
-    let total_time = stream.end_time.checked_sub(stream.start_time).unwrap() as u64;
-
-    let streamed_amount = (stream.amount as u128)
-        .checked_mul(elapsed_time as u128).unwrap()
-        .checked_div(total_time as u128).unwrap() as u64;
+    let required_lamports = rent_exempt_lamports.saturating_sub(current_lamports);
+    if required_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer { from: payer, to: account.clone() },
+            ),
+            required_lamports,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate { account_to_allocate: account.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign { account_to_assign: account },
+            signer_seeds,
+        ),
+        owner,
+    )
+}
 
-    Ok(streamed_amount)
+/// Close `account`, owned by this program, the way Anchor's `close` does
+pub(crate) fn close_pda_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false).map_err(Into::into)
 }
 
 #[derive(Accounts)]
-#[instruction(recipient: Pubkey, start_time: i64)]
-pub struct InitializeStream<'info> {
-    #[account(
-        init,
-        payer = sender,
-        space = 8 + size_of::<Stream>(),
-        seeds = [
-            b"stream",
-            sender.key().as_ref(),
-            recipient.as_ref(),
-            mint.key().as_ref(),
-            &start_time.to_le_bytes()
-        ],
-        bump
-    )]
-    pub stream: Account<'info, Stream>,
-
-    #[account(mut)]
-    pub sender: Signer<'info>,
-
-    pub mint: Account<'info, Mint>,
-
+pub struct TransferStream<'info> {
     #[account(
         mut,
-        constraint = sender_token_account.mint == mint.key(),
-        constraint = sender_token_account.owner == sender.key()
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch
     )]
-    pub sender_token_account: Account<'info, TokenAccount>,
+    pub stream: Account<'info, Stream>,
 
     #[account(
-        init,
-        payer = sender,
-        token::mint = mint,
-        token::authority = stream,
-        seeds = [
-            b"escrow",
-            stream.key().as_ref()
-        ],
-        bump
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub config: Account<'info, Config>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
-
-    pub recipient: Signer<'info>,
-
+pub struct UpdateStream<'info> {
     #[account(
         mut,
-        constraint = escrow_token_account.key() == stream.escrow_token_account
+        constraint = stream.version == STREAM_VERSION @ StreamFlowError::VersionMismatch
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub stream: Account<'info, Stream>,
 
     #[account(
-        mut,
-        constraint = recipient_token_account.mint == escrow_token_account.mint,
-        constraint = recipient_token_account.owner == recipient.key()
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.emergency_paused @ StreamFlowError::EmergencyPauseActive,
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub config: Account<'info, Config>,
 
-    pub token_program: Program<'info, Token>,
+    pub authority: Signer<'info>,
 }
 
-#[derive(Accounts)]
-pub struct CancelStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+#[event]
+pub struct StreamTransferred {
+    pub stream: Pubkey,
+    pub old_recipient: Pubkey,
+    pub new_recipient: Pubkey,
+    pub transferred_by: Pubkey,
+}
 
-    pub authority: Signer<'info>,
+#[event]
+pub struct StreamUpdated {
+    pub stream: Pubkey,
+    pub updated_by: Pubkey,
+}
 
-    #[account(
-        mut,
-        constraint = escrow_token_account.key() == stream.escrow_token_account
-    )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::stream::tests::test_stream;
+
+    #[test]
+    fn linear_stream_releases_unlocks_then_the_remainder() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        stream.initial_unlock_amount = 100;
+        stream.cliff_unlock_amount = 200;
+        stream.cliff_time = Some(1_500);
+
+        // Nothing before the start, the initial unlock from the start until the cliff
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 999).unwrap(), 0);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_000).unwrap(), 100);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_499).unwrap(), 100);
+
+        // At the cliff the cliff unlock and the remainder accrued since the start are released
+        assert_eq!(calculate_vested_remainder(&stream, None, None, 1_500).unwrap(), 350);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_500).unwrap(), 650);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_750).unwrap(), 825);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 2_000).unwrap(), 1_000);
+    }
 
-    #[account(
-        mut,
-        constraint = recipient_token_account.mint == escrow_token_account.mint
-    )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[test]
+    fn periodic_stream_releases_whole_tranches() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 0, 10 * 86_400);
+        stream.release_frequency = WithdrawFrequency::Daily;
+        stream.initial_unlock_amount = 500;
 
-    #[account(
-        mut,
-        constraint = sender_token_account.mint == escrow_token_account.mint
-    )]
-    pub sender_token_account: Account<'info, TokenAccount>,
+        assert_eq!(calculate_vested_remainder(&stream, None, None, 86_399).unwrap(), 0);
+        assert_eq!(calculate_vested_remainder(&stream, None, None, 86_400).unwrap(), 50);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 3 * 86_400 + 1).unwrap(), 650);
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    #[test]
+    fn cliff_and_unlock_streams() {
+        let mut stream = test_stream(StreamType::Cliff, 1_000, 1_000, 2_000);
+        stream.cliff_time = Some(1_500);
+        stream.initial_unlock_amount = 100;
 
-#[derive(Accounts)]
-pub struct TransferStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_499).unwrap(), 100);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_500).unwrap(), 1_000);
 
-    pub authority: Signer<'info>,
-}
+        let mut stream = test_stream(StreamType::Unlock, 1_000, 1_000, 2_000);
+        stream.initial_unlock_amount = 100;
 
-#[derive(Accounts)]
-pub struct UpdateStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 1_999).unwrap(), 100);
+        assert_eq!(calculate_streamed_amount(&stream, None, None, 2_000).unwrap(), 1_000);
+    }
 
-    pub authority: Signer<'info>,
-}
+    #[test]
+    fn rate_stream_is_capped_at_its_deposit() {
+        let mut stream = test_stream(StreamType::Rate, 1_000, 1_000, 1_100);
+        stream.rate_per_second = 10;
+
+        assert_eq!(calculate_vested_remainder(&stream, None, None, 1_050).unwrap(), 500);
+        assert_eq!(calculate_vested_remainder(&stream, None, None, 1_200).unwrap(), 1_000);
+    }
 
-#[account]
-pub struct Stream {
-    pub sender: Pubkey,
-    pub recipient: Pubkey,
-    pub mint: Pubkey,
-    pub escrow_token_account: Pubkey,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub cliff_time: Option<i64>,
-    pub amount: u64,
-    pub withdrawn_amount:
\ No newline at end of file
+    #[test]
+    fn schedule_streams_need_their_schedule() {
+        let stream = test_stream(StreamType::Schedule, 1_000, 1_000, 2_000);
+        assert!(calculate_vested_remainder(&stream, None, None, 1_500).is_err());
+
+        let stream = test_stream(StreamType::Milestone, 1_000, 1_000, 2_000);
+        assert!(calculate_vested_remainder(&stream, None, None, 1_500).is_err());
+    }
+
+    #[test]
+    fn paused_and_cancelled_streams_stop_accruing() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        stream.status = StreamStatus::Paused;
+        stream.paused_at = 1_200;
+        assert_eq!(calculate_available_amount(&stream, None, None, 1_800).unwrap(), 200);
+
+        stream.status = StreamStatus::Cancelled;
+        stream.canceled_at = Some(1_400);
+        stream.withdrawn_amount = 100;
+        assert_eq!(calculate_available_amount(&stream, None, None, 1_800).unwrap(), 300);
+    }
+}
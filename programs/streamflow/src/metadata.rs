@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::pubkey;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::StreamFlowError;
+use crate::state::Stream;
+
+/// The Metaplex token metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Metaplex instruction index of `CreateMetadataAccountV3`
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+
+/// Metaplex instruction index of `CreateMasterEditionV3`
+const CREATE_MASTER_EDITION_V3: u8 = 17;
+
+/// Longest name Metaplex accepts
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Longest URI Metaplex accepts
+pub const MAX_URI_LEN: usize = 200;
+
+/// Symbol of every stream position NFT
+pub const STREAM_NFT_SYMBOL: &str = "STREAM";
+
+/// Borsh layout of Metaplex's `DataV2`.
+///
+/// Creators, collection and uses are never set, so they are typed as `Option<()>`:
+/// a `None` serializes to the same single tag byte whatever the inner type.
+#[derive(AnchorSerialize)]
+struct DataV2 {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<()>,
+    collection: Option<()>,
+    uses: Option<()>,
+}
+
+#[derive(AnchorSerialize)]
+struct CreateMetadataAccountArgsV3 {
+    data: DataV2,
+    is_mutable: bool,
+    collection_details: Option<()>,
+}
+
+#[derive(AnchorSerialize)]
+struct CreateMasterEditionArgs {
+    max_supply: Option<u64>,
+}
+
+/// The mint of a stream's position NFT, which exists if the stream was created with one.
+///
+/// Mints cannot be closed, so `created_at` keeps a stream that reuses a closed
+/// stream's address from inheriting its NFT.
+pub fn stream_nft_mint(stream_key: &Pubkey, stream: &Stream) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"stream_nft", stream_key.as_ref(), &stream.created_at.to_le_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// The account entitled to a stream's vested tokens.
+///
+/// That is `stream.recipient`, unless the stream is represented by an NFT, in
+/// which case it is whoever owns `nft_token_account` holding that NFT.
+pub fn stream_recipient(
+    stream_key: &Pubkey,
+    stream: &Stream,
+    nft_token_account: Option<&InterfaceAccount<TokenAccount>>,
+) -> Result<Pubkey> {
+    if !stream.nft_recipient {
+        return Ok(stream.recipient);
+    }
+
+    let nft_token_account = nft_token_account.ok_or(StreamFlowError::InvalidRecipient)?;
+    require!(
+        nft_token_account.mint == stream_nft_mint(stream_key, stream) && nft_token_account.amount == 1,
+        StreamFlowError::InvalidRecipient
+    );
+    Ok(nft_token_account.owner)
+}
+
+/// Create the Metaplex metadata account of `mint`.
+///
+/// `authority` is both mint and update authority and signs with `authority_seeds`.
+pub fn create_metadata_account<'info>(
+    token_metadata_program: AccountInfo<'info>,
+    metadata: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    rent: AccountInfo<'info>,
+    name: String,
+    uri: String,
+    authority_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let args = CreateMetadataAccountArgsV3 {
+        data: DataV2 {
+            name,
+            symbol: STREAM_NFT_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        is_mutable: false,
+        collection_details: None,
+    };
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    args.serialize(&mut data)?;
+
+    let instruction = Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(metadata.key(), false),
+            AccountMeta::new_readonly(mint.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[metadata, mint, authority, payer, system_program, rent, token_metadata_program],
+        authority_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Create the master edition of `mint` with a max supply of 0, making it a 1-of-1.
+///
+/// Metaplex moves the mint and freeze authority of `mint` to `edition`.
+pub fn create_master_edition<'info>(
+    token_metadata_program: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    metadata: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    rent: AccountInfo<'info>,
+    authority_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = vec![CREATE_MASTER_EDITION_V3];
+    CreateMasterEditionArgs { max_supply: Some(0) }.serialize(&mut data)?;
+
+    let instruction = Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(edition.key(), false),
+            AccountMeta::new(mint.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new(metadata.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            edition,
+            mint,
+            authority,
+            payer,
+            metadata,
+            token_program,
+            system_program,
+            rent,
+            token_metadata_program,
+        ],
+        authority_seeds,
+    )
+    .map_err(Into::into)
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::FeeConfig;
+
+/// Program-wide settings, stored in the PDA at `[b"config"]`
+#[account]
+pub struct Config {
+    /// The account allowed to change the config
+    pub admin: Pubkey,
+    /// Protocol fees charged on create, withdraw and cancel
+    pub fee_config: FeeConfig,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// The account allowed to trigger the emergency pause besides the admin
+    pub pause_guardian: Pubkey,
+    /// Whether the emergency pause is engaged
+    pub emergency_paused: bool,
+    /// `PAUSE_EXEMPT_*` flags for instructions that keep running while paused
+    pub pause_exemptions: u8,
+    /// Share of each `crank_withdraw` paid to the keeper, in basis points
+    pub crank_tip_rate: u16,
+    /// Most streams a single `create_streams_batch` call may open
+    pub max_batch_size: u8,
+    /// Seconds a queued `ConfigProposal` waits before it can be executed
+    pub timelock_delay: i64,
+    /// Number of config proposals queued so far, used to derive the next one
+    pub proposal_count: u64,
+    /// Reserved space for future upgrades
+    pub reserved: [u8; 11],
+}
+
+impl Config {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        FeeConfig::LEN + // fee_config
+        1 + // bump
+        32 + // pause_guardian
+        1 + // emergency_paused
+        1 + // pause_exemptions
+        2 + // crank_tip_rate
+        1 + // max_batch_size
+        8 + // timelock_delay
+        8 + // proposal_count
+        11; // reserved
+
+    /// Upper bound for `max_batch_size`, what fits in one 1232-byte transaction.
+    ///
+    /// The fixed accounts, signature and blockhash take about 480 bytes and each
+    /// entry adds its stream and escrow keys plus roughly 110-140 bytes of params,
+    /// so about 4 entries fit. A transaction that loads the stream and escrow
+    /// addresses from an address lookup table pays 1 byte per key instead of 32,
+    /// but this cap still applies.
+    pub const MAX_BATCH_SIZE: u8 = 4;
+
+    /// Recipients can still withdraw vested tokens during an emergency pause
+    pub const PAUSE_EXEMPT_WITHDRAW: u8 = 1 << 0;
+    /// Streams can still be cancelled during an emergency pause
+    pub const PAUSE_EXEMPT_CANCEL: u8 = 1 << 1;
+
+    /// Shortest notice stream users get before protocol settings change
+    pub const MIN_TIMELOCK_DELAY: i64 = 2 * 24 * 60 * 60;
+    /// Longest delay, so the admin cannot lock settings in place indefinitely
+    pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+
+    pub fn validate_timelock_delay(timelock_delay: i64) -> Result<()> {
+        require!(
+            (Self::MIN_TIMELOCK_DELAY..=Self::MAX_TIMELOCK_DELAY).contains(&timelock_delay),
+            StreamFlowError::InvalidTimelockDelay
+        );
+        Ok(())
+    }
+
+    /// Delay applied to proposals queued now, configs created before the timelock start at the minimum
+    pub fn effective_timelock_delay(&self) -> i64 {
+        std::cmp::max(self.timelock_delay, Self::MIN_TIMELOCK_DELAY)
+    }
+
+    /// Largest batch `create_streams_batch` accepts, configs created before batching read 0 and get the cap
+    pub fn effective_max_batch_size(&self) -> u8 {
+        match self.max_batch_size {
+            0 => Self::MAX_BATCH_SIZE,
+            max_batch_size => max_batch_size,
+        }
+    }
+
+    /// Whether an instruction exempted by `exemption` may run right now
+    pub fn allows(&self, exemption: u8) -> bool {
+        !self.emergency_paused || self.pause_exemptions & exemption != 0
+    }
+
+    pub fn is_pause_authority(&self, key: &Pubkey) -> bool {
+        *key == self.admin || *key == self.pause_guardian
+    }
+}
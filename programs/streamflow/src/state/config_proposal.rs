@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::{Config, FeeConfig, MintListMode};
+
+/// Protocol-level setting a `ConfigProposal` changes once its delay has passed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum ConfigChange {
+    /// Replace fee rates and the fee recipient
+    FeeConfig { fee_config: FeeConfig },
+    /// Hand the config over to a new admin
+    Admin { admin: Pubkey },
+    /// Change the keeper tip paid by `crank_withdraw`
+    CrankTipRate { crank_tip_rate: u16 },
+    /// Change the per-call limit of `create_streams_batch`
+    MaxBatchSize { max_batch_size: u8 },
+    /// Change the delay of proposals queued from now on
+    TimelockDelay { timelock_delay: i64 },
+    /// Change how the mint registry screens mints, creating the registry if it does not exist yet
+    MintRegistry {
+        mode: MintListMode,
+        max_decimals: u8,
+        reject_freezable: bool,
+    },
+    /// List `mint` in the mint registry
+    AddMintEntry { mint: Pubkey },
+    /// Remove `mint` from the mint registry
+    RemoveMintEntry { mint: Pubkey },
+    /// Change the account allowed to trigger the emergency pause besides the admin
+    PauseGuardian { pause_guardian: Pubkey },
+    /// Sweep `amount` of collected fees of `mint` to the fee recipient
+    WithdrawTreasury { mint: Pubkey, amount: u64 },
+}
+
+impl ConfigChange {
+    /// Size of the largest variant, `WithdrawTreasury`
+    pub const LEN: usize = 1 + 32 + 8;
+
+    /// Validate the new values before they are queued
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ConfigChange::FeeConfig { fee_config } => fee_config.validate(),
+            ConfigChange::Admin { admin } => {
+                require!(*admin != Pubkey::default(), StreamFlowError::InvalidTimelockProposal);
+                Ok(())
+            },
+            ConfigChange::CrankTipRate { crank_tip_rate } => {
+                require!(*crank_tip_rate <= FeeConfig::MAX_FEE_RATE, StreamFlowError::InvalidFeeConfig);
+                Ok(())
+            },
+            ConfigChange::MaxBatchSize { max_batch_size } => {
+                require!(
+                    *max_batch_size > 0 && *max_batch_size <= Config::MAX_BATCH_SIZE,
+                    StreamFlowError::BatchOperationLimitExceeded
+                );
+                Ok(())
+            },
+            ConfigChange::TimelockDelay { timelock_delay } => Config::validate_timelock_delay(*timelock_delay),
+            ConfigChange::WithdrawTreasury { amount, .. } => {
+                require!(*amount > 0, StreamFlowError::InvalidWithdrawalAmount);
+                Ok(())
+            },
+            ConfigChange::MintRegistry { .. }
+            | ConfigChange::AddMintEntry { .. }
+            | ConfigChange::RemoveMintEntry { .. }
+            | ConfigChange::PauseGuardian { .. } => Ok(()),
+        }
+    }
+}
+
+/// A queued `ConfigChange`, stored in the PDA at `[b"config_proposal", index]`
+#[account]
+pub struct ConfigProposal {
+    /// Position in the config's proposal sequence
+    pub index: u64,
+    /// The change applied on execution
+    pub change: ConfigChange,
+    /// The admin that queued the change, or the governance PDA
+    pub proposer: Pubkey,
+    /// When the change was queued (Unix timestamp)
+    pub queued_at: i64,
+    /// Earliest time the change can be executed (Unix timestamp)
+    pub eta: i64,
+    /// When the change was executed, 0 while pending
+    pub executed_at: i64,
+    /// When the change was cancelled, 0 while pending
+    pub cancelled_at: i64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl ConfigProposal {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // index
+        ConfigChange::LEN + // change
+        32 + // proposer
+        8 + // queued_at
+        8 + // eta
+        8 + // executed_at
+        8 + // cancelled_at
+        1; // bump
+
+    /// Fill a freshly created proposal with `change`, returning its eta
+    pub fn queue(
+        &mut self,
+        config: &mut Config,
+        change: ConfigChange,
+        proposer: Pubkey,
+        bump: u8,
+        current_time: i64,
+    ) -> Result<i64> {
+        change.validate()?;
+
+        let eta = current_time
+            .checked_add(config.effective_timelock_delay())
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+        self.index = config.proposal_count;
+        self.change = change;
+        self.proposer = proposer;
+        self.queued_at = current_time;
+        self.eta = eta;
+        self.executed_at = 0;
+        self.cancelled_at = 0;
+        self.bump = bump;
+
+        config.proposal_count = config.proposal_count
+            .checked_add(1)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+        Ok(eta)
+    }
+}
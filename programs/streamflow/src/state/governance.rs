@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::ConfigChange;
+
+/// Token-holder governance, stored in the PDA at `[b"governance"]`.
+///
+/// Once the config admin is handed over to this PDA, passed proposals are
+/// the only way to queue `ConfigChange`s through the timelock.
+#[account]
+pub struct Governance {
+    /// Mint of the token that carries voting power
+    pub mint: Pubkey,
+    /// Token account holding the tokens locked in votes
+    pub vault: Pubkey,
+    /// Votes, for and against, a proposal needs to be decided
+    pub quorum_votes: u64,
+    /// Seconds a proposal is open for voting
+    pub voting_period: i64,
+    /// Number of proposals created so far, used to derive the next one
+    pub proposal_count: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Bump seed for the vault PDA
+    pub vault_bump: u8,
+}
+
+impl Governance {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // vault
+        8 + // quorum_votes
+        8 + // voting_period
+        8 + // proposal_count
+        1 + // bump
+        1; // vault_bump
+
+    /// The governance PDA, which proposes the config changes it queues
+    pub fn address() -> Pubkey {
+        Pubkey::find_program_address(&[b"governance"], &crate::ID).0
+    }
+
+    /// Shortest voting period, so token holders have time to react
+    pub const MIN_VOTING_PERIOD: i64 = 24 * 60 * 60;
+
+    pub fn validate_params(quorum_votes: u64, voting_period: i64) -> Result<()> {
+        require!(
+            quorum_votes > 0 && voting_period >= Self::MIN_VOTING_PERIOD,
+            StreamFlowError::InvalidGovernanceProposal
+        );
+        Ok(())
+    }
+}
+
+/// A `ConfigChange` put to a token-holder vote
+#[account]
+pub struct GovernanceProposal {
+    /// Position in the governance proposal sequence
+    pub index: u64,
+    /// The token holder that created the proposal
+    pub proposer: Pubkey,
+    /// The change queued through the timelock if the proposal passes
+    pub change: ConfigChange,
+    /// End of the voting period (Unix timestamp)
+    pub voting_ends_at: i64,
+    /// Votes in favour
+    pub yes_votes: u64,
+    /// Votes against
+    pub no_votes: u64,
+    /// Whether the passed change has been queued in the timelock
+    pub queued: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl GovernanceProposal {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // index
+        32 + // proposer
+        ConfigChange::LEN + // change
+        8 + // voting_ends_at
+        8 + // yes_votes
+        8 + // no_votes
+        1 + // queued
+        1; // bump
+
+    /// Record `votes` for or against the proposal
+    pub fn record_vote(&mut self, approve: bool, votes: u64) -> Result<()> {
+        let tally = if approve { &mut self.yes_votes } else { &mut self.no_votes };
+        *tally = tally
+            .checked_add(votes)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        Ok(())
+    }
+
+    /// Check that voting is over, quorum was reached and the majority approved
+    pub fn check_passed(&self, quorum_votes: u64, current_time: i64) -> Result<()> {
+        require!(current_time >= self.voting_ends_at, StreamFlowError::GovernanceVotingPeriodActive);
+
+        let total_votes = self.yes_votes
+            .checked_add(self.no_votes)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        require!(total_votes >= quorum_votes, StreamFlowError::QuorumNotReached);
+        require!(self.yes_votes > self.no_votes, StreamFlowError::GovernanceProposalNotPassed);
+
+        Ok(())
+    }
+}
+
+/// One token holder's vote on a proposal, holding their tokens until voting ends
+#[account]
+pub struct VoteRecord {
+    /// The proposal voted on
+    pub proposal: Pubkey,
+    /// The token holder that voted
+    pub voter: Pubkey,
+    /// Tokens locked in the vault for this vote
+    pub votes: u64,
+    /// Whether the vote is in favour
+    pub approve: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // proposal
+        32 + // voter
+        8 + // votes
+        1 + // approve
+        1; // bump
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+
+/// Maximum number of tranches a single milestone schedule can hold
+pub const MAX_MILESTONES: usize = 16;
+
+/// A tranche released once the schedule's approver signs off on it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Milestone {
+    /// Tokens released by this milestone
+    pub amount: u64,
+    /// When the milestone was approved (Unix timestamp), 0 until then
+    pub approved_at: i64,
+}
+
+impl Milestone {
+    pub const LEN: usize = 8 + // amount
+        8; // approved_at
+
+    pub fn is_approved(&self) -> bool {
+        self.approved_at != 0
+    }
+}
+
+/// Tranches of a `StreamType::Milestone` stream and who may approve them
+#[account]
+pub struct MilestoneSchedule {
+    /// The stream this schedule belongs to
+    pub stream: Pubkey,
+    /// The sender, a third-party arbiter or a multisig PDA
+    pub approver: Pubkey,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Tranches in the order they are expected to be delivered
+    pub milestones: Vec<Milestone>,
+}
+
+impl MilestoneSchedule {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // stream
+        32 + // approver
+        1 + // bump
+        4 + MAX_MILESTONES * Milestone::LEN; // milestones
+
+    /// Validate tranche amounts against the stream they will govern
+    pub fn validate_amounts(amounts: &[u64], amount: u64) -> Result<()> {
+        require!(
+            !amounts.is_empty() && amounts.len() <= MAX_MILESTONES,
+            StreamFlowError::InvalidMilestoneParameters
+        );
+        require!(
+            amounts.iter().all(|tranche| *tranche > 0),
+            StreamFlowError::InvalidMilestoneParameters
+        );
+
+        let total = amounts
+            .iter()
+            .try_fold(0u64, |total, tranche| total.checked_add(*tranche))
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        require!(total == amount, StreamFlowError::InvalidMilestoneParameters);
+
+        Ok(())
+    }
+
+    /// Mark milestone `index` approved at `current_time` and return its amount
+    pub fn approve(&mut self, index: u8, current_time: i64) -> Result<u64> {
+        let milestone = self
+            .milestones
+            .get_mut(index as usize)
+            .ok_or(StreamFlowError::InvalidMilestoneParameters)?;
+
+        require!(!milestone.is_approved(), StreamFlowError::MilestoneAlreadyClaimed);
+        milestone.approved_at = current_time;
+
+        Ok(milestone.amount)
+    }
+
+    /// Total of the milestones approved at or before `current_time`
+    pub fn approved_amount(&self, current_time: i64) -> Result<u64> {
+        self.milestones
+            .iter()
+            .filter(|milestone| milestone.is_approved() && milestone.approved_at <= current_time)
+            .try_fold(0u64, |total, milestone| total.checked_add(milestone.amount))
+            .ok_or(StreamFlowError::MathematicalOverflow.into())
+    }
+}
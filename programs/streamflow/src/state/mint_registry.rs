@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::error::StreamFlowError;
+use crate::state::MintListMode;
+
+/// Rules for which mints can be streamed, stored in the PDA at `[b"mint_registry"]`
+#[account]
+pub struct MintRegistry {
+    /// How `MintEntry` accounts are interpreted
+    pub mode: MintListMode,
+    /// Highest number of decimals a streamed mint may have
+    pub max_decimals: u8,
+    /// Refuse mints with a freeze authority, which could freeze escrows
+    pub reject_freezable: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl MintRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        1 + // mode
+        1 + // max_decimals
+        1 + // reject_freezable
+        1; // bump
+
+    /// Check `mint` against the registry, `listed` is whether its `MintEntry` exists
+    pub fn check_mint(&self, mint: &Mint, listed: bool) -> Result<()> {
+        match self.mode {
+            MintListMode::Disabled => {},
+            MintListMode::Allowlist => require!(listed, StreamFlowError::TokenNotWhitelisted),
+            MintListMode::Blocklist => require!(!listed, StreamFlowError::BlacklistedToken),
+        }
+
+        require!(mint.decimals <= self.max_decimals, StreamFlowError::InvalidTokenDecimals);
+        require!(
+            !self.reject_freezable || mint.freeze_authority.is_none(),
+            StreamFlowError::UnsupportedToken
+        );
+
+        Ok(())
+    }
+}
+
+/// Marks a mint as listed in the `MintRegistry`, stored in the PDA at `[b"mint_entry", mint]`
+#[account]
+pub struct MintEntry {
+    /// The listed mint
+    pub mint: Pubkey,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl MintEntry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        1; // bump
+
+    /// Whether the account at a mint's entry address holds a `MintEntry`
+    pub fn is_listed(mint_entry: &AccountInfo) -> bool {
+        *mint_entry.owner == crate::ID && !mint_entry.data_is_empty()
+    }
+}
@@ -1,15 +1,36 @@
-```rust
 use anchor_lang::prelude::*;
 
+use crate::error::StreamFlowError;
+
+pub mod config;
+pub mod config_proposal;
+pub mod governance;
+pub mod milestone_schedule;
+pub mod mint_registry;
+pub mod multisig;
+pub mod oracle;
+pub mod price_condition;
 pub mod stream;
-pub mod escrow;
+pub mod stream_template;
 pub mod treasury;
+pub mod vesting_schedule;
+pub mod withdraw_settings;
 
+pub use config::*;
+pub use config_proposal::*;
+pub use governance::*;
+pub use milestone_schedule::*;
+pub use mint_registry::*;
+pub use multisig::*;
+pub use oracle::*;
+pub use price_condition::*;
 pub use stream::*;
-pub use escrow::*;
+pub use stream_template::*;
 pub use treasury::*;
+pub use vesting_schedule::*;
+pub use withdraw_settings::*;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StreamStatus {
     Active,
     Paused,
@@ -17,14 +38,20 @@ pub enum StreamStatus {
     Completed,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StreamType {
     Linear,
     Cliff,
     Unlock,
+    /// Open-ended stream paying `rate_per_second` until the deposit runs out
+    Rate,
+    /// Piecewise curve defined by a `VestingSchedule` account
+    Schedule,
+    /// Tranches released by an approver through a `MilestoneSchedule` account
+    Milestone,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WithdrawFrequency {
     Continuous,
     Daily,
@@ -34,6 +61,32 @@ pub enum WithdrawFrequency {
     Yearly,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnlockAmount {
+    /// An absolute number of tokens
+    Amount(u64),
+    /// A share of the stream amount in basis points (10_000 = 100%)
+    BasisPoints(u16),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintListMode {
+    /// Every mint is accepted
+    Disabled,
+    /// Only mints with a `MintEntry` are accepted
+    Allowlist,
+    /// Mints with a `MintEntry` are refused
+    Blocklist,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopUpMode {
+    /// Keep the current rate and push `end_time` out
+    ExtendDuration,
+    /// Keep `end_time` and release the extra tokens faster from now on
+    IncreaseRate,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct StreamConfig {
     pub stream_type: StreamType,
@@ -46,9 +99,52 @@ pub struct StreamConfig {
     pub can_update: bool,
     pub automatic_withdrawal: bool,
     pub withdrawal_public: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub cancelable_by_recipient: bool,
+}
+
+/// The curve or tranches a `StreamType::Schedule` or `StreamType::Milestone` stream is opened with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum StreamSchedule {
+    /// Checkpoints of the stream's `VestingSchedule`
+    Checkpoints { checkpoints: Vec<Checkpoint>, interpolate: bool },
+    /// Tranche amounts of the stream's `MilestoneSchedule` and who approves them
+    Milestones { approver: Pubkey, amounts: Vec<u64> },
+}
+
+/// Metadata of the position NFT a stream can be created with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PositionNft {
+    pub name: String,
+    pub uri: String,
 }
 
+/// Everything needed to open a stream besides its accounts
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StreamParams {
+    pub stream_id: String,
+    /// Tokens the sender deposits, before any transfer fee
+    pub amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub cliff_time: Option<i64>,
+    pub stream_type: StreamType,
+    pub release_frequency: WithdrawFrequency,
+    pub rate_per_second: u64,
+    pub initial_unlock: UnlockAmount,
+    pub cliff_unlock: UnlockAmount,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub can_pause: bool,
+    pub automatic_withdrawal: bool,
+    /// Whether anyone may trigger a withdrawal to the holder's own token account
+    pub withdrawal_public: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct StreamMetrics {
     pub total_deposited: u64,
     pub total_withdrawn: u64,
@@ -90,32 +186,50 @@ impl Default for StreamConfig {
             can_update: false,
             automatic_withdrawal: false,
             withdrawal_public: false,
+            transferable_by_sender: false,
+            transferable_by_recipient: false,
+            cancelable_by_recipient: false,
         }
     }
 }
 
-impl Default for StreamMetrics {
-    fn default() -> Self {
-        Self {
-            total_deposited: 0,
-            total_withdrawn: 0,
-            last_withdrawal_time: 0,
-            withdrawal_count: 0,
-            pause_count: 0,
-            total_paused_duration: 0,
-        }
+impl StreamConfig {
+    pub const LEN: usize = 1 + // stream_type
+        8 + // start_time
+        8 + // end_time
+        1 + 8 + // cliff_time
+        1 + // withdraw_frequency
+        1 + // can_cancel
+        1 + // can_pause
+        1 + // can_update
+        1 + // automatic_withdrawal
+        1 + // withdrawal_public
+        1 + // transferable_by_sender
+        1 + // transferable_by_recipient
+        1; // cancelable_by_recipient
+
+    /// Copy of a config whose times are relative to 0, moved to begin at `start_time`
+    pub fn starting_at(&self, start_time: i64) -> Result<Self> {
+        let shift = |time: i64| {
+            time.checked_add(start_time)
+                .ok_or(StreamFlowError::MathematicalOverflow)
+        };
+        Ok(Self {
+            start_time: shift(self.start_time)?,
+            end_time: shift(self.end_time)?,
+            cliff_time: self.cliff_time.map(shift).transpose()?,
+            ..self.clone()
+        })
     }
-}
 
-impl StreamConfig {
     pub fn validate(&self) -> Result<()> {
-        require!(self.start_time > 0, StreamflowError::InvalidStartTime);
-        require!(self.end_time > self.start_time, StreamflowError::InvalidEndTime);
+        require!(self.start_time > 0, StreamFlowError::InvalidTimestamp);
+        require!(self.end_time > self.start_time, StreamFlowError::EndTimeBeforeStartTime);
         
         if let Some(cliff_time) = self.cliff_time {
             require!(
                 cliff_time >= self.start_time && cliff_time <= self.end_time,
-                StreamflowError::InvalidCliffTime
+                StreamFlowError::InvalidCliffDuration
             );
         }
         
@@ -147,7 +261,7 @@ impl StreamConfig {
         }
         
         match self.stream_type {
-            StreamType::Linear => {
+            StreamType::Linear | StreamType::Rate => {
                 let elapsed = current_time - self.start_time;
                 let duration = self.duration();
                 ((total_amount as u128 * elapsed as u128) / duration as u128) as u64
@@ -159,7 +273,8 @@ impl StreamConfig {
                     0
                 }
             },
-            StreamType::Unlock => {
+            // Checkpoints and milestones live in their own accounts, so only the end is known here
+            StreamType::Unlock | StreamType::Schedule | StreamType::Milestone => {
                 if current_time >= self.end_time {
                     total_amount
                 } else {
@@ -170,7 +285,52 @@ impl StreamConfig {
     }
 }
 
+impl StreamParams {
+    pub const LEN: usize = 4 + MAX_STREAM_ID_LEN + // stream_id
+        8 + // amount
+        8 + // start_time
+        8 + // end_time
+        1 + 8 + // cliff_time
+        1 + // stream_type
+        1 + // release_frequency
+        8 + // rate_per_second
+        1 + 8 + // initial_unlock
+        1 + 8 + // cliff_unlock
+        1 + // cancelable_by_sender
+        1 + // cancelable_by_recipient
+        1 + // transferable_by_sender
+        1 + // transferable_by_recipient
+        1 + // can_pause
+        1 + // automatic_withdrawal
+        1; // withdrawal_public
+}
+
+impl UnlockAmount {
+    pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+    /// Resolve to a token amount out of `total_amount`
+    pub fn resolve(&self, total_amount: u64) -> Result<u64> {
+        match *self {
+            UnlockAmount::Amount(amount) => Ok(amount),
+            UnlockAmount::BasisPoints(basis_points) => {
+                require!(
+                    basis_points <= Self::MAX_BASIS_POINTS,
+                    StreamFlowError::UnlockPercentageExceedsMaximum
+                );
+                Ok((total_amount as u128 * basis_points as u128 / Self::MAX_BASIS_POINTS as u128) as u64)
+            },
+        }
+    }
+}
+
 impl StreamMetrics {
+    pub const LEN: usize = 8 + // total_deposited
+        8 + // total_withdrawn
+        8 + // last_withdrawal_time
+        4 + // withdrawal_count
+        4 + // pause_count
+        8; // total_paused_duration
+
     pub fn update_withdrawal(&mut self, amount: u64, current_time: i64) {
         self.total_withdrawn = self.total_withdrawn.saturating_add(amount);
         self.last_withdrawal_time = current_time;
@@ -187,45 +347,255 @@ impl StreamMetrics {
     }
 }
 
-#[error_code]
-pub enum StreamflowError {
-    #[msg("Invalid start time")]
-    InvalidStartTime,
-    #[msg("Invalid end time")]
-    InvalidEndTime,
-    #[msg("Invalid cliff time")]
-    InvalidCliffTime,
-    #[msg("Stream not active")]
-    StreamNotActive,
-    #[msg("Stream already started")]
-    StreamAlreadyStarted,
-    #[msg("Stream not started")]
-    StreamNotStarted,
-    #[msg("Stream already completed")]
-    StreamAlreadyCompleted,
-    #[msg("Insufficient funds")]
-    InsufficientFunds,
-    #[msg("Unauthorized")]
-    Unauthorized,
-    #[msg("Invalid amount")]
-    InvalidAmount,
-    #[msg("Cliff not reached")]
-    CliffNotReached,
-    #[msg("Cannot cancel stream")]
-    CannotCancelStream,
-    #[msg("Cannot pause stream")]
-    CannotPauseStream,
-    #[msg("Cannot update stream")]
-    CannotUpdateStream,
-    #[msg("Invalid recipient")]
-    InvalidRecipient,
-    #[msg("Invalid token mint")]
-    InvalidTokenMint,
-    #[msg("Math overflow")]
-    MathOverflow,
-    #[msg("Invalid fee configuration")]
-    InvalidFeeConfig,
-    #[msg("Fee calculation error")]
-    FeeCalculationError,
-}
-```
\ No newline at end of file
+impl FeeConfig {
+    pub const LEN: usize = 2 + // platform_fee_rate
+        2 + // withdrawal_fee_rate
+        2 + // cancellation_fee_rate
+        32; // fee_recipient
+
+    /// Highest rate any fee can be set to, in basis points (10%)
+    pub const MAX_FEE_RATE: u16 = 1_000;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.platform_fee_rate <= Self::MAX_FEE_RATE
+                && self.withdrawal_fee_rate <= Self::MAX_FEE_RATE
+                && self.cancellation_fee_rate <= Self::MAX_FEE_RATE,
+            StreamFlowError::InvalidFeeConfig
+        );
+        require!(self.fee_recipient != Pubkey::default(), StreamFlowError::InvalidFeeConfig);
+        Ok(())
+    }
+
+    /// Fee owed on `amount` at `fee_rate` basis points, rounded down
+    pub fn calculate_fee(amount: u64, fee_rate: u16) -> Result<u64> {
+        (amount as u128)
+            .checked_mul(fee_rate as u128)
+            .map(|result| result / UnlockAmount::MAX_BASIS_POINTS as u128)
+            .and_then(|result| u64::try_from(result).ok())
+            .ok_or(StreamFlowError::FeeCalculationError.into())
+    }
+}
+
+pub(crate) const SECONDS_PER_DAY: i64 = 86_400;
+
+impl WithdrawFrequency {
+    /// The time of the `period`-th unlock after `start_time`.
+    ///
+    /// Monthly, quarterly and yearly boundaries follow the calendar: they land on
+    /// the same day of month and time of day as `start_time`, clamped to the last
+    /// day of shorter months. `Continuous` is treated as one period per second.
+    pub fn period_boundary(&self, start_time: i64, period: u64) -> Option<i64> {
+        let period = i64::try_from(period).ok()?;
+        let seconds = |length: i64| start_time.checked_add(period.checked_mul(length)?);
+
+        match self {
+            WithdrawFrequency::Continuous => seconds(1),
+            WithdrawFrequency::Daily => seconds(SECONDS_PER_DAY),
+            WithdrawFrequency::Weekly => seconds(7 * SECONDS_PER_DAY),
+            WithdrawFrequency::Monthly => add_months(start_time, period),
+            WithdrawFrequency::Quarterly => add_months(start_time, period.checked_mul(3)?),
+            WithdrawFrequency::Yearly => add_months(start_time, period.checked_mul(12)?),
+        }
+    }
+
+    /// Number of period boundaries reached between `start_time` and `current_time`
+    pub fn periods_elapsed(&self, start_time: i64, current_time: i64) -> Option<u64> {
+        if current_time <= start_time {
+            return Some(0);
+        }
+
+        let elapsed = current_time - start_time;
+        let months = |step: i64| {
+            let (start_year, start_month, _) = civil_from_days(start_time.div_euclid(SECONDS_PER_DAY));
+            let (year, month, _) = civil_from_days(current_time.div_euclid(SECONDS_PER_DAY));
+            let mut months = (year - start_year) * 12 + (month - start_month);
+            if add_months(start_time, months)? > current_time {
+                months -= 1;
+            }
+            u64::try_from(months / step).ok()
+        };
+
+        match self {
+            WithdrawFrequency::Continuous => u64::try_from(elapsed).ok(),
+            WithdrawFrequency::Daily => u64::try_from(elapsed / SECONDS_PER_DAY).ok(),
+            WithdrawFrequency::Weekly => u64::try_from(elapsed / (7 * SECONDS_PER_DAY)).ok(),
+            WithdrawFrequency::Monthly => months(1),
+            WithdrawFrequency::Quarterly => months(3),
+            WithdrawFrequency::Yearly => months(12),
+        }
+    }
+
+    /// Number of tranches in a schedule, counting a final partial period ending at `end_time`
+    pub fn period_count(&self, start_time: i64, end_time: i64) -> Option<u64> {
+        let full_periods = self.periods_elapsed(start_time, end_time)?;
+        if self.period_boundary(start_time, full_periods)? < end_time {
+            full_periods.checked_add(1)
+        } else {
+            Some(full_periods)
+        }
+    }
+}
+
+/// Add calendar months to a timestamp, keeping the time of day and clamping the day of month
+fn add_months(timestamp: i64, months: i64) -> Option<i64> {
+    let days = timestamp.div_euclid(SECONDS_PER_DAY);
+    let time_of_day = timestamp.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    let month_index = (month - 1).checked_add(months)?;
+    let year = year.checked_add(month_index.div_euclid(12))?;
+    let month = month_index.rem_euclid(12) + 1;
+    let day = std::cmp::min(day, days_in_month(year, month));
+
+    days_from_civil(year, month, day)
+        .checked_mul(SECONDS_PER_DAY)?
+        .checked_add(time_of_day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian (year, month, day) for a count of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(year: i64, month: i64, day: i64) -> i64 {
+        days_from_civil(year, month, day) * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(2023));
+        assert!(!is_leap_year(1900));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn month_end_is_clamped_without_drifting() {
+        let start = timestamp(2024, 1, 31) + 12 * 3_600;
+        let monthly = WithdrawFrequency::Monthly;
+
+        assert_eq!(monthly.period_boundary(start, 1), Some(timestamp(2024, 2, 29) + 12 * 3_600));
+        assert_eq!(monthly.period_boundary(start, 2), Some(timestamp(2024, 3, 31) + 12 * 3_600));
+        assert_eq!(monthly.period_boundary(start, 3), Some(timestamp(2024, 4, 30) + 12 * 3_600));
+        assert_eq!(add_months(timestamp(2023, 1, 31), 1), Some(timestamp(2023, 2, 28)));
+        assert_eq!(add_months(timestamp(2024, 3, 31), -1), Some(timestamp(2024, 2, 29)));
+    }
+
+    #[test]
+    fn leap_day_anniversaries() {
+        let start = timestamp(2024, 2, 29);
+        let yearly = WithdrawFrequency::Yearly;
+
+        assert_eq!(yearly.period_boundary(start, 1), Some(timestamp(2025, 2, 28)));
+        assert_eq!(yearly.period_boundary(start, 4), Some(timestamp(2028, 2, 29)));
+        assert_eq!(yearly.periods_elapsed(start, timestamp(2025, 2, 28)), Some(1));
+        assert_eq!(yearly.periods_elapsed(start, timestamp(2025, 2, 28) - 1), Some(0));
+        assert_eq!(WithdrawFrequency::Quarterly.period_boundary(timestamp(2023, 11, 30), 1), Some(timestamp(2024, 2, 29)));
+    }
+
+    #[test]
+    fn periods_elapsed_on_period_edges() {
+        let start = timestamp(2024, 1, 31) + 12 * 3_600;
+        let monthly = WithdrawFrequency::Monthly;
+
+        assert_eq!(monthly.periods_elapsed(start, start), Some(0));
+        assert_eq!(monthly.periods_elapsed(start, start - 1), Some(0));
+        for period in 1..=24 {
+            let boundary = monthly.period_boundary(start, period).unwrap();
+            assert_eq!(monthly.periods_elapsed(start, boundary), Some(period));
+            assert_eq!(monthly.periods_elapsed(start, boundary - 1), Some(period - 1));
+        }
+
+        let daily = WithdrawFrequency::Daily;
+        assert_eq!(daily.periods_elapsed(0, SECONDS_PER_DAY), Some(1));
+        assert_eq!(daily.periods_elapsed(0, SECONDS_PER_DAY - 1), Some(0));
+        assert_eq!(WithdrawFrequency::Continuous.periods_elapsed(10, 25), Some(15));
+    }
+
+    #[test]
+    fn period_count_includes_partial_final_period() {
+        let monthly = WithdrawFrequency::Monthly;
+        let start = timestamp(2024, 1, 15);
+
+        assert_eq!(monthly.period_count(start, timestamp(2024, 4, 15)), Some(3));
+        assert_eq!(monthly.period_count(start, timestamp(2024, 4, 1)), Some(3));
+        assert_eq!(monthly.period_count(start, timestamp(2024, 4, 15) + 1), Some(4));
+        assert_eq!(monthly.period_count(start, timestamp(2025, 1, 15)), Some(12));
+
+        let weekly = WithdrawFrequency::Weekly;
+        assert_eq!(weekly.period_count(0, 14 * SECONDS_PER_DAY), Some(2));
+        assert_eq!(weekly.period_count(0, 15 * SECONDS_PER_DAY), Some(3));
+    }
+
+    #[test]
+    fn fees_are_basis_points_rounded_down() {
+        assert_eq!(FeeConfig::calculate_fee(1_000_000, 25).unwrap(), 2_500);
+        assert_eq!(FeeConfig::calculate_fee(399, 25).unwrap(), 0);
+        assert_eq!(FeeConfig::calculate_fee(401, 25).unwrap(), 1);
+        assert_eq!(FeeConfig::calculate_fee(1_000, 0).unwrap(), 0);
+        assert_eq!(FeeConfig::calculate_fee(u64::MAX, FeeConfig::MAX_FEE_RATE).unwrap(), u64::MAX / 10);
+    }
+
+    #[test]
+    fn fee_config_caps_rates_and_needs_a_recipient() {
+        let fee_config = FeeConfig {
+            fee_recipient: Pubkey::new_unique(),
+            ..FeeConfig::default()
+        };
+        assert!(fee_config.validate().is_ok());
+        assert!(FeeConfig::default().validate().is_err());
+        assert!(FeeConfig { withdrawal_fee_rate: FeeConfig::MAX_FEE_RATE + 1, ..fee_config }.validate().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::StreamParams;
+
+/// Maximum number of signers a multisig can have
+pub const MAX_MULTISIG_SIGNERS: usize = 16;
+
+/// M-of-N signer set whose `[b"multisig_signer", multisig]` PDA can act as a
+/// stream's sender once enough signers approve a proposal
+#[account]
+pub struct Multisig {
+    /// One-off key the multisig address is derived from
+    pub create_key: Pubkey,
+    /// Keys allowed to propose, approve and execute
+    pub signers: Vec<Pubkey>,
+    /// Approvals a proposal needs before it can be executed
+    pub threshold: u8,
+    /// Number of proposals created so far, used to derive the next one
+    pub proposal_count: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Bump seed for the signer PDA
+    pub signer_bump: u8,
+}
+
+impl Multisig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // create_key
+        4 + MAX_MULTISIG_SIGNERS * 32 + // signers
+        1 + // threshold
+        8 + // proposal_count
+        1 + // bump
+        1; // signer_bump
+
+    /// Validate a signer set and its threshold
+    pub fn validate_signers(signers: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+            StreamFlowError::InvalidMultisigThreshold
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            StreamFlowError::InvalidMultisigThreshold
+        );
+        for (index, signer) in signers.iter().enumerate() {
+            require!(
+                !signers[..index].contains(signer),
+                StreamFlowError::DuplicateMultisigSigner
+            );
+        }
+        Ok(())
+    }
+
+    /// Position of `key` in the signer set
+    pub fn signer_index(&self, key: &Pubkey) -> Result<usize> {
+        self.signers
+            .iter()
+            .position(|signer| signer == key)
+            .ok_or(StreamFlowError::MultisigSignerNotFound.into())
+    }
+}
+
+/// Stream operation a multisig can perform as the stream's sender
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum MultisigAction {
+    CreateStream { params: StreamParams },
+    CancelStream,
+    UpdateStream {
+        cancelable_by_sender: Option<bool>,
+        cancelable_by_recipient: Option<bool>,
+        transferable_by_sender: Option<bool>,
+        transferable_by_recipient: Option<bool>,
+    },
+    TransferStream { new_recipient: Pubkey },
+}
+
+impl MultisigAction {
+    /// Size of the largest variant
+    pub const LEN: usize = 1 + StreamParams::LEN;
+}
+
+/// A pending `MultisigAction` and the signers that approved it
+#[account]
+pub struct MultisigProposal {
+    /// The multisig this proposal belongs to
+    pub multisig: Pubkey,
+    /// Position in the multisig's proposal sequence
+    pub index: u64,
+    /// The signer that created the proposal
+    pub proposer: Pubkey,
+    /// The stream the action applies to
+    pub stream: Pubkey,
+    /// What executing the proposal does
+    pub action: MultisigAction,
+    /// Approval flag per signer, in the multisig's signer order
+    pub approvals: Vec<bool>,
+    /// Whether the proposal has been executed
+    pub executed: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl MultisigProposal {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // multisig
+        8 + // index
+        32 + // proposer
+        32 + // stream
+        MultisigAction::LEN + // action
+        4 + MAX_MULTISIG_SIGNERS + // approvals
+        1 + // executed
+        1; // bump
+
+    pub fn approval_count(&self) -> usize {
+        self.approvals.iter().filter(|approved| **approved).count()
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+use arrayref::array_ref;
+
+use crate::error::StreamFlowError;
+
+/// The Pyth oracle program that owns v2 price accounts
+pub const PYTH_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/// Magic number at the start of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Pyth account type tag for price accounts
+pub const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+
+/// Pyth aggregate status meaning the price is currently being published
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Minimum size of a Pyth price account, up to the end of the aggregate price
+const PYTH_PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// A simple price account owned by this program, for feeds without a Pyth publisher
+#[account]
+pub struct PriceFeed {
+    /// The only account allowed to publish prices
+    pub authority: Pubkey,
+    /// Latest price, scaled by `10^expo`
+    pub price: i64,
+    /// Price exponent
+    pub expo: i32,
+    /// When the latest price was published (Unix timestamp)
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        8 + // price
+        4 + // expo
+        8; // publish_time
+}
+
+/// A price read from either a Pyth price account or a `PriceFeed`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Read the current price from `price_feed`, rejecting prices older than `max_staleness` seconds.
+    ///
+    /// The account must be a Pyth price account or a `PriceFeed` of this program.
+    pub fn load(price_feed: &AccountInfo, current_time: i64, max_staleness: i64) -> Result<Self> {
+        let data = price_feed.try_borrow_data()?;
+        let oracle_price = if *price_feed.owner == crate::ID {
+            let price_feed = PriceFeed::try_deserialize(&mut &data[..])
+                .map_err(|_| StreamFlowError::OraclePriceFeedError)?;
+            Self {
+                price: price_feed.price,
+                expo: price_feed.expo,
+                publish_time: price_feed.publish_time,
+            }
+        } else {
+            // Anyone can write Pyth-shaped bytes into an account they own
+            require_keys_eq!(*price_feed.owner, PYTH_PROGRAM_ID, StreamFlowError::OraclePriceFeedError);
+            Self::load_pyth(&data)?
+        };
+
+        require!(oracle_price.publish_time > 0, StreamFlowError::PriceFeedUnavailable);
+        require!(oracle_price.price > 0, StreamFlowError::PriceFeedUnavailable);
+        require!(
+            current_time.saturating_sub(oracle_price.publish_time) <= max_staleness,
+            StreamFlowError::PriceFeedStale
+        );
+
+        Ok(oracle_price)
+    }
+
+    /// Parse the aggregate price of a Pyth v2 price account
+    fn load_pyth(data: &[u8]) -> Result<Self> {
+        require!(data.len() >= PYTH_PRICE_ACCOUNT_MIN_LEN, StreamFlowError::OraclePriceFeedError);
+
+        let magic = u32::from_le_bytes(*array_ref![data, 0, 4]);
+        let account_type = u32::from_le_bytes(*array_ref![data, 8, 4]);
+        require!(
+            magic == PYTH_MAGIC && account_type == PYTH_PRICE_ACCOUNT_TYPE,
+            StreamFlowError::OraclePriceFeedError
+        );
+
+        let status = u32::from_le_bytes(*array_ref![data, 224, 4]);
+        require!(status == PYTH_STATUS_TRADING, StreamFlowError::PriceFeedUnavailable);
+
+        Ok(Self {
+            price: i64::from_le_bytes(*array_ref![data, 208, 8]),
+            expo: i32::from_le_bytes(*array_ref![data, 20, 4]),
+            publish_time: i64::from_le_bytes(*array_ref![data, 96, 8]),
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::MAX_MILESTONES;
+
+/// Oracle thresholds that release the tranches of a milestone stream.
+///
+/// The stream's `MilestoneSchedule` names this account as its approver, so
+/// tranches can only be approved by proving the price condition on-chain.
+#[account]
+pub struct PriceCondition {
+    /// The stream this condition belongs to
+    pub stream: Pubkey,
+    /// Pyth price account or `PriceFeed` the thresholds are checked against
+    pub price_feed: Pubkey,
+    /// Exponent the thresholds are expressed in, must match the feed
+    pub expo: i32,
+    /// Maximum age in seconds of a price that can unlock a tranche
+    pub max_staleness: i64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Price each milestone needs to exceed, by milestone index
+    pub thresholds: Vec<i64>,
+}
+
+impl PriceCondition {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // stream
+        32 + // price_feed
+        4 + // expo
+        8 + // max_staleness
+        1 + // bump
+        4 + MAX_MILESTONES * 8; // thresholds
+
+    /// Validate thresholds against the milestones they will release
+    pub fn validate_thresholds(thresholds: &[i64], milestone_count: usize, max_staleness: i64) -> Result<()> {
+        require!(thresholds.len() == milestone_count, StreamFlowError::InvalidPriceThreshold);
+        require!(
+            thresholds.iter().all(|threshold| *threshold > 0),
+            StreamFlowError::InvalidPriceThreshold
+        );
+        require!(max_staleness > 0, StreamFlowError::InvalidPriceThreshold);
+
+        Ok(())
+    }
+}
@@ -1,126 +1,171 @@
-```rust
 use anchor_lang::prelude::*;
 
+use crate::error::StreamFlowError;
+use crate::state::{StreamMetrics, StreamStatus, StreamType, WithdrawFrequency, SECONDS_PER_DAY};
+
+/// Layout version written into every `Stream` account created by this program
+pub const STREAM_VERSION: u8 = 2;
+
+/// Maximum length in bytes of a client-supplied stream identifier
+pub const MAX_STREAM_ID_LEN: usize = 32;
+
+/// The single on-chain representation of a token stream.
+///
+/// Fixed-size fields come first so that clients can filter on `version`,
+/// `sender`, `recipient` and `mint` with memcmp at stable offsets.
 #[account]
 pub struct Stream {
-    /// The authority that can modify the stream
-    pub authority: Pubkey,
+    /// Layout version, see `STREAM_VERSION`
+    pub version: u8,
+    /// Bump seed for the stream PDA
+    pub bump: u8,
+    /// Bump seed for the escrow token account PDA
+    pub escrow_bump: u8,
+    /// The account that funded the stream
+    pub sender: Pubkey,
     /// The recipient of the stream
     pub recipient: Pubkey,
     /// The mint of the token being streamed
     pub mint: Pubkey,
     /// The token account holding the streamed tokens
-    pub token_account: Pubkey,
-    /// The total amount of tokens in the stream
-    pub total_amount: u64,
+    pub escrow_token_account: Pubkey,
+    /// The total amount of tokens deposited into the stream
+    pub amount: u64,
     /// The amount of tokens already withdrawn
     pub withdrawn_amount: u64,
     /// The start time of the stream (Unix timestamp)
     pub start_time: i64,
     /// The end time of the stream (Unix timestamp)
     pub end_time: i64,
+    /// The time the stream was created (Unix timestamp)
+    pub created_at: i64,
+    /// The last time tokens were withdrawn
+    pub last_withdrawn_at: i64,
+    /// How tokens are released over the life of the stream
+    pub stream_type: StreamType,
+    /// Tranche schedule for linear streams, `Continuous` releases every second
+    pub release_frequency: WithdrawFrequency,
+    /// Lifecycle state of the stream
+    pub status: StreamStatus,
+    /// Whether the sender can cancel the stream
+    pub cancelable_by_sender: bool,
+    /// Whether the recipient can cancel the stream
+    pub cancelable_by_recipient: bool,
+    /// Whether the sender can transfer the stream to another recipient
+    pub transferable_by_sender: bool,
+    /// Whether the recipient can transfer the stream to another recipient
+    pub transferable_by_recipient: bool,
     /// The cliff time (Unix timestamp) - tokens cannot be withdrawn before this
-    pub cliff_time: i64,
-    /// Whether the stream can be cancelled by the authority
-    pub cancelable: bool,
-    /// Whether the stream has been cancelled
-    pub cancelled: bool,
+    pub cliff_time: Option<i64>,
     /// The time when the stream was cancelled (if applicable)
-    pub cancelled_at: i64,
-    /// The name/description of the stream
-    pub name: [u8; 64],
-    /// The category of the stream (0: Vesting, 1: Streaming, 2: Lock)
-    pub category: u8,
-    /// Whether the recipient can transfer the stream to another address
-    pub transferable: bool,
-    /// The rate at which tokens are released per second
+    pub canceled_at: Option<i64>,
+    /// Client-supplied identifier, at most `MAX_STREAM_ID_LEN` bytes
+    pub stream_id: String,
+    /// Whether the sender can pause and resume the stream
+    pub can_pause: bool,
+    /// The time the stream was paused, 0 while it is running
+    pub paused_at: i64,
+    /// Deposit, withdrawal and pause bookkeeping
+    pub metrics: StreamMetrics,
+    /// Tokens released per second for `StreamType::Rate` streams, 0 otherwise
     pub rate_per_second: u64,
-    /// The last time tokens were withdrawn
-    pub last_withdrawn_at: i64,
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    /// Tokens released as soon as the stream starts
+    pub initial_unlock_amount: u64,
+    /// Tokens released as a lump sum when the cliff is reached
+    pub cliff_unlock_amount: u64,
+    /// Whether keepers may push vested tokens to the recipient with `crank_withdraw`
+    pub automatic_withdrawal: bool,
+    /// Whether the holder of the stream's position NFT, not `recipient`, is entitled to withdraw
+    pub nft_recipient: bool,
+    /// Whether anyone may trigger a withdrawal to the holder's own token account
+    pub withdrawal_public: bool,
     /// Reserved space for future upgrades
-    pub reserved: [u8; 128],
+    pub reserved: [u8; 52],
 }
 
 impl Stream {
     pub const LEN: usize = 8 + // discriminator
-        32 + // authority
+        1 + // version
+        1 + // bump
+        1 + // escrow_bump
+        32 + // sender
         32 + // recipient
         32 + // mint
-        32 + // token_account
-        8 + // total_amount
+        32 + // escrow_token_account
+        8 + // amount
         8 + // withdrawn_amount
         8 + // start_time
         8 + // end_time
-        8 + // cliff_time
-        1 + // cancelable
-        1 + // cancelled
-        8 + // cancelled_at
-        64 + // name
-        1 + // category
-        1 + // transferable
-        8 + // rate_per_second
+        8 + // created_at
         8 + // last_withdrawn_at
-        1 + // bump
-        128; // reserved
-
-    /// Calculate the amount of tokens that can be withdrawn at the current time
-    pub fn withdrawable_amount(&self, current_time: i64) -> Result<u64> {
-        if self.cancelled {
-            return Ok(0);
-        }
-
-        if current_time < self.cliff_time {
-            return Ok(0);
-        }
-
-        let effective_start = std::cmp::max(self.start_time, self.cliff_time);
-        let effective_current = std::cmp::min(current_time, self.end_time);
-
-        if effective_current <= effective_start {
-            return Ok(0);
-        }
-
-        let elapsed_time = effective_current - effective_start;
-        let total_duration = self.end_time - effective_start;
+        1 + // stream_type
+        1 + // release_frequency
+        1 + // status
+        1 + // cancelable_by_sender
+        1 + // cancelable_by_recipient
+        1 + // transferable_by_sender
+        1 + // transferable_by_recipient
+        1 + 8 + // cliff_time
+        1 + 8 + // canceled_at
+        4 + MAX_STREAM_ID_LEN + // stream_id
+        1 + // can_pause
+        8 + // paused_at
+        StreamMetrics::LEN + // metrics
+        8 + // rate_per_second
+        8 + // initial_unlock_amount
+        8 + // cliff_unlock_amount
+        1 + // automatic_withdrawal
+        1 + // nft_recipient
+        1 + // withdrawal_public
+        52; // reserved
 
-        if total_duration <= 0 {
-            return Ok(self.total_amount.saturating_sub(self.withdrawn_amount));
-        }
+    /// The time at which a rate stream funded with `amount` runs dry
+    pub fn runway_end(start_time: i64, amount: u64, rate_per_second: u64) -> Result<i64> {
+        require!(rate_per_second > 0, StreamFlowError::InvalidRatePerSecond);
 
-        let vested_amount = (self.total_amount as u128)
-            .checked_mul(elapsed_time as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(total_duration as u128)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let runway = amount
+            .checked_add(rate_per_second - 1)
+            .map(|result| result / rate_per_second)
+            .and_then(|result| i64::try_from(result).ok())
+            .ok_or(StreamFlowError::StreamRateCalculationError)?;
 
-        Ok(vested_amount.saturating_sub(self.withdrawn_amount))
+        start_time
+            .checked_add(runway)
+            .ok_or(StreamFlowError::StreamRateCalculationError.into())
     }
 
     /// Check if the stream is active (not cancelled and within time bounds)
     pub fn is_active(&self, current_time: i64) -> bool {
-        !self.cancelled && current_time >= self.start_time && current_time <= self.end_time
+        self.status == StreamStatus::Active
+            && current_time >= self.start_time
+            && current_time <= self.end_time
     }
 
     /// Check if the stream has ended
     pub fn is_ended(&self, current_time: i64) -> bool {
-        current_time > self.end_time || self.cancelled
+        current_time > self.end_time || self.status == StreamStatus::Cancelled
     }
 
     /// Check if the stream is fully withdrawn
     pub fn is_fully_withdrawn(&self) -> bool {
-        self.withdrawn_amount >= self.total_amount
+        self.withdrawn_amount >= self.amount
+    }
+
+    /// The part of `amount` released by the stream type rather than the up-front unlocks
+    pub fn vesting_amount(&self) -> u64 {
+        self.amount
+            .saturating_sub(self.initial_unlock_amount)
+            .saturating_sub(self.cliff_unlock_amount)
     }
 
     /// Get the remaining amount in the stream
     pub fn remaining_amount(&self) -> u64 {
-        self.total_amount.saturating_sub(self.withdrawn_amount)
+        self.amount.saturating_sub(self.withdrawn_amount)
     }
 
     /// Calculate the progress of the stream as a percentage (0-100)
     pub fn progress_percentage(&self, current_time: i64) -> u8 {
-        if self.cancelled || current_time < self.start_time {
+        if self.status == StreamStatus::Cancelled || current_time < self.start_time {
             return 0;
         }
 
@@ -143,91 +188,295 @@ impl Stream {
     pub fn validate_params(
         start_time: i64,
         end_time: i64,
-        cliff_time: i64,
-        total_amount: u64,
+        cliff_time: Option<i64>,
+        amount: u64,
     ) -> Result<()> {
-        require!(start_time > 0, ErrorCode::InvalidStartTime);
-        require!(end_time > start_time, ErrorCode::InvalidEndTime);
-        require!(cliff_time >= start_time, ErrorCode::InvalidCliffTime);
-        require!(cliff_time <= end_time, ErrorCode::InvalidCliffTime);
-        require!(total_amount > 0, ErrorCode::InvalidAmount);
+        require!(start_time > 0, StreamFlowError::InvalidTimestamp);
+        require!(end_time > start_time, StreamFlowError::EndTimeBeforeStartTime);
+        require!(amount > 0, StreamFlowError::ZeroStreamAmount);
+
+        if let Some(cliff) = cliff_time {
+            require!(cliff >= start_time, StreamFlowError::CliffTimeBeforeStartTime);
+            require!(cliff <= end_time, StreamFlowError::CliffExceedsStreamDuration);
+        }
+
         Ok(())
     }
 
+    /// The last moment at which tokens accrue: cancellation or pause freezes the schedule
+    pub fn accrual_cutoff(&self, current_time: i64) -> i64 {
+        match (self.status, self.canceled_at) {
+            (_, Some(canceled_at)) => std::cmp::min(canceled_at, current_time),
+            (StreamStatus::Paused, None) => std::cmp::min(self.paused_at, current_time),
+            _ => current_time,
+        }
+    }
+
+    /// Seconds of a pause that fall inside the schedule and must be excluded from accrual
+    pub fn effective_pause_duration(&self, resumed_at: i64) -> i64 {
+        let paused_from = std::cmp::max(self.paused_at, self.start_time);
+        std::cmp::max(resumed_at - paused_from, 0)
+    }
+
+    /// Shift the schedule forward so paused time is never counted as vested
+    pub fn shift_schedule(&mut self, duration: i64) -> Result<()> {
+        self.start_time = self.start_time
+            .checked_add(duration)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        self.end_time = self.end_time
+            .checked_add(duration)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        if let Some(cliff_time) = self.cliff_time {
+            self.cliff_time = Some(
+                cliff_time
+                    .checked_add(duration)
+                    .ok_or(StreamFlowError::MathematicalOverflow)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// `end_time` after adding `added_amount` at the current rate.
+    ///
+    /// `end - start` grows in proportion to the vesting amount, so the rate is unchanged.
+    pub fn extended_end_time(&self, added_amount: u64) -> Result<i64> {
+        let duration = self.end_time - self.start_time;
+        let extension = (added_amount as u128)
+            .checked_mul(duration as u128)
+            .and_then(|result| result.checked_div(self.vesting_amount() as u128))
+            .and_then(|result| i64::try_from(result).ok())
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+        self.end_time
+            .checked_add(extension)
+            .ok_or(StreamFlowError::MathematicalOverflow.into())
+    }
+
+    /// `start_time` after adding `added_amount` at `current_time` without moving `end_time`.
+    ///
+    /// The new line passes through what has already vested at `current_time`
+    /// and reaches the new vesting amount at `end_time`.
+    pub fn increased_rate_start_time(&self, added_amount: u64, current_time: i64) -> Result<i64> {
+        let vesting_amount = self.vesting_amount() as u128;
+        let new_vesting_amount = vesting_amount + added_amount as u128;
+        let elapsed_time = current_time - self.start_time;
+        let vested_amount = vesting_amount * elapsed_time as u128
+            / (self.end_time - self.start_time) as u128;
+        let remaining_time = self.end_time - current_time;
+        let span = new_vesting_amount
+            .checked_mul(remaining_time as u128)
+            .and_then(|result| result.checked_div(new_vesting_amount - vested_amount))
+            .and_then(|result| i64::try_from(result).ok())
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+        Ok(self.end_time - span)
+    }
+
     /// Update the stream after withdrawal
     pub fn update_after_withdrawal(&mut self, amount: u64, current_time: i64) -> Result<()> {
         self.withdrawn_amount = self.withdrawn_amount
             .checked_add(amount)
-            .ok_or(ErrorCode::MathOverflow)?;
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
         self.last_withdrawn_at = current_time;
+        self.metrics.update_withdrawal(amount, current_time);
         Ok(())
     }
+}
 
-    /// Cancel the stream
-    pub fn cancel(&mut self, current_time: i64) -> Result<()> {
-        require!(self.cancelable, ErrorCode::StreamNotCancelable);
-        require!(!self.cancelled, ErrorCode::StreamAlreadyCancelled);
-        
-        self.cancelled = true;
-        self.cancelled_at = current_time;
-        Ok(())
+/// Layout of `Stream` accounts written with version 1, read by `migrate_stream`.
+///
+/// Version 1 stored `release_frequency` as a step in seconds and had none of
+/// the unlock, crank, NFT or public withdrawal fields.
+#[derive(AnchorDeserialize)]
+pub struct StreamV1 {
+    pub version: u8,
+    pub bump: u8,
+    pub escrow_bump: u8,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub amount: u64,
+    pub withdrawn_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_at: i64,
+    pub last_withdrawn_at: i64,
+    pub release_frequency: u64,
+    pub stream_type: StreamType,
+    pub status: StreamStatus,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub cliff_time: Option<i64>,
+    pub canceled_at: Option<i64>,
+    pub stream_id: String,
+    pub can_pause: bool,
+    pub paused_at: i64,
+    pub metrics: StreamMetrics,
+    pub rate_per_second: u64,
+}
+
+impl StreamV1 {
+    pub const VERSION: u8 = 1;
+
+    /// The version 1 step as a `WithdrawFrequency`.
+    ///
+    /// Only steps with an exact counterpart carry over. Once a stream is over
+    /// its step no longer affects what vested, so any step becomes `Continuous`.
+    pub fn withdraw_frequency(&self, current_time: i64) -> Result<WithdrawFrequency> {
+        const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY as u64;
+
+        let ended = self.status == StreamStatus::Cancelled
+            || (self.status != StreamStatus::Paused && current_time >= self.end_time);
+        match self.release_frequency {
+            0 => Ok(WithdrawFrequency::Continuous),
+            step if step == SECONDS_PER_DAY as u64 => Ok(WithdrawFrequency::Daily),
+            SECONDS_PER_WEEK => Ok(WithdrawFrequency::Weekly),
+            _ if ended => Ok(WithdrawFrequency::Continuous),
+            _ => err!(StreamFlowError::InvalidLinearReleaseParameters),
+        }
     }
 
-    /// Transfer the stream to a new recipient
-    pub fn transfer_to(&mut self, new_recipient: Pubkey) -> Result<()> {
-        require!(self.transferable, ErrorCode::StreamNotTransferable);
-        require!(!self.cancelled, ErrorCode::StreamCancelled);
-        
-        self.recipient = new_recipient;
-        Ok(())
+    /// The same stream in the current layout
+    pub fn migrate(self, current_time: i64) -> Result<Stream> {
+        Ok(Stream {
+            version: STREAM_VERSION,
+            release_frequency: self.withdraw_frequency(current_time)?,
+            bump: self.bump,
+            escrow_bump: self.escrow_bump,
+            sender: self.sender,
+            recipient: self.recipient,
+            mint: self.mint,
+            escrow_token_account: self.escrow_token_account,
+            amount: self.amount,
+            withdrawn_amount: self.withdrawn_amount,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            created_at: self.created_at,
+            last_withdrawn_at: self.last_withdrawn_at,
+            stream_type: self.stream_type,
+            status: self.status,
+            cancelable_by_sender: self.cancelable_by_sender,
+            cancelable_by_recipient: self.cancelable_by_recipient,
+            transferable_by_sender: self.transferable_by_sender,
+            transferable_by_recipient: self.transferable_by_recipient,
+            cliff_time: self.cliff_time,
+            canceled_at: self.canceled_at,
+            stream_id: self.stream_id,
+            can_pause: self.can_pause,
+            paused_at: self.paused_at,
+            metrics: self.metrics,
+            rate_per_second: self.rate_per_second,
+            initial_unlock_amount: 0,
+            cliff_unlock_amount: 0,
+            automatic_withdrawal: false,
+            nft_recipient: false,
+            withdrawal_public: false,
+            reserved: [0; 52],
+        })
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum StreamCategory {
-    Vesting = 0,
-    Streaming = 1,
-    Lock = 2,
-}
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
 
-impl From<u8> for StreamCategory {
-    fn from(value: u8) -> Self {
-        match value {
-            0 => StreamCategory::Vesting,
-            1 => StreamCategory::Streaming,
-            2 => StreamCategory::Lock,
-            _ => StreamCategory::Streaming,
+    /// A continuous stream of `amount` from `start_time` to `end_time` with every option off
+    pub(crate) fn test_stream(stream_type: StreamType, amount: u64, start_time: i64, end_time: i64) -> Stream {
+        Stream {
+            version: STREAM_VERSION,
+            bump: 255,
+            escrow_bump: 255,
+            sender: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            escrow_token_account: Pubkey::new_unique(),
+            amount,
+            withdrawn_amount: 0,
+            start_time,
+            end_time,
+            created_at: start_time,
+            last_withdrawn_at: 0,
+            stream_type,
+            release_frequency: WithdrawFrequency::Continuous,
+            status: StreamStatus::Active,
+            cancelable_by_sender: false,
+            cancelable_by_recipient: false,
+            transferable_by_sender: false,
+            transferable_by_recipient: false,
+            cliff_time: None,
+            canceled_at: None,
+            stream_id: String::from("test"),
+            can_pause: true,
+            paused_at: 0,
+            metrics: StreamMetrics::default(),
+            rate_per_second: 0,
+            initial_unlock_amount: 0,
+            cliff_unlock_amount: 0,
+            automatic_withdrawal: false,
+            nft_recipient: false,
+            withdrawal_public: false,
+            reserved: [0; 52],
         }
     }
-}
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid start time")]
-    InvalidStartTime,
-    #[msg("Invalid end time")]
-    InvalidEndTime,
-    #[msg("Invalid cliff time")]
-    InvalidCliffTime,
-    #[msg("Invalid amount")]
-    InvalidAmount,
-    #[msg("Math overflow")]
-    MathOverflow,
-    #[msg("Stream is not cancelable")]
-    StreamNotCancelable,
-    #[msg("Stream is already cancelled")]
-    StreamAlreadyCancelled,
-    #[msg("Stream is cancelled")]
-    StreamCancelled,
-    #[msg("Stream is not transferable")]
-    StreamNotTransferable,
-    #[msg("Insufficient withdrawable amount")]
-    InsufficientWithdrawableAmount,
-    #[msg("Stream has not started yet")]
-    StreamNotStarted,
-    #[msg("Stream has ended")]
-    StreamEnded,
-    #[msg("Cliff period not reached")]
-    CliffNotReached,
+    #[test]
+    fn pause_counts_only_time_inside_the_schedule() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+
+        stream.paused_at = 1_200;
+        assert_eq!(stream.effective_pause_duration(1_500), 300);
+
+        // Paused before the start: only the part after `start_time` delays vesting
+        stream.paused_at = 500;
+        assert_eq!(stream.effective_pause_duration(1_300), 300);
+
+        // Resumed before the start: nothing was delayed
+        assert_eq!(stream.effective_pause_duration(800), 0);
+    }
+
+    #[test]
+    fn shift_schedule_moves_start_end_and_cliff() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        stream.cliff_time = Some(1_250);
+
+        stream.shift_schedule(300).unwrap();
+        assert_eq!((stream.start_time, stream.end_time, stream.cliff_time), (1_300, 2_300, Some(1_550)));
+
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        stream.shift_schedule(300).unwrap();
+        assert_eq!(stream.cliff_time, None);
+    }
+
+    #[test]
+    fn extension_keeps_the_rate() {
+        let stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        assert_eq!(stream.extended_end_time(500).unwrap(), 2_500);
+
+        // Up-front unlocks are not part of the rate
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, 2_000);
+        stream.initial_unlock_amount = 500;
+        assert_eq!(stream.extended_end_time(500).unwrap(), 3_000);
+    }
+
+    #[test]
+    fn rate_increase_keeps_what_already_vested() {
+        let stream = test_stream(StreamType::Linear, 1_000, 0, 1_000);
+
+        // 500 vested at t=500, the new line runs from 250 to 1_000 over 1_500 tokens
+        let start_time = stream.increased_rate_start_time(500, 500).unwrap();
+        assert_eq!(start_time, 250);
+        assert_eq!(1_500 * (500 - start_time) / (1_000 - start_time), 500);
+
+        // Before anything vested the new line simply starts at the old start
+        assert_eq!(stream.increased_rate_start_time(500, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn shift_schedule_rejects_overflow() {
+        let mut stream = test_stream(StreamType::Linear, 1_000, 1_000, i64::MAX - 10);
+        assert!(stream.shift_schedule(100).is_err());
+    }
 }
-```
\ No newline at end of file
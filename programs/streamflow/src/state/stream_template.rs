@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::{StreamConfig, StreamType, MAX_STREAM_ID_LEN};
+
+/// Reusable stream settings a sender can open streams from.
+///
+/// Times in `config` are offsets from the stream start, so `config.start_time`
+/// is always 0 and `config.end_time` is the stream duration.
+#[account]
+pub struct StreamTemplate {
+    /// The sender allowed to open streams from this template
+    pub sender: Pubkey,
+    /// Client-supplied identifier, at most `MAX_STREAM_ID_LEN` bytes
+    pub template_id: String,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Settings every stream opened from this template shares
+    pub config: StreamConfig,
+}
+
+impl StreamTemplate {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // sender
+        4 + MAX_STREAM_ID_LEN + // template_id
+        1 + // bump
+        StreamConfig::LEN; // config
+
+    /// Validate a relative config so that any start time in the future yields a valid stream
+    pub fn validate_config(config: &StreamConfig, current_time: i64) -> Result<()> {
+        require!(config.start_time == 0, StreamFlowError::InvalidStreamTemplate);
+        // Rate streams derive their end from a per-second rate the template does not hold
+        require!(config.stream_type != StreamType::Rate, StreamFlowError::InvalidStreamTemplate);
+        // Streams have no update flag, so a template cannot promise one
+        require!(!config.can_update, StreamFlowError::InvalidStreamTemplate);
+
+        config.starting_at(current_time)?.validate()
+    }
+}
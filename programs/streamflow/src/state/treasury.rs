@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+
+/// Protocol fee revenue for one mint, kept apart from user escrows
+#[account]
+pub struct Treasury {
+    /// The mint whose fees this treasury collects
+    pub mint: Pubkey,
+    /// Token account holding the collected fees, owned by this treasury
+    pub vault: Pubkey,
+    /// Lifetime fees received
+    pub total_collected: u64,
+    /// Lifetime fees swept out by the admin
+    pub total_withdrawn: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Bump seed for the fee vault PDA
+    pub vault_bump: u8,
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // vault
+        8 + // total_collected
+        8 + // total_withdrawn
+        1 + // bump
+        1; // vault_bump
+
+    pub fn record_collection(&mut self, amount: u64) -> Result<()> {
+        self.total_collected = self.total_collected
+            .checked_add(amount)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self.total_withdrawn
+            .checked_add(amount)
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn treasury_tracks_collections_and_withdrawals() {
+        let mut treasury = Treasury {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            total_collected: 0,
+            total_withdrawn: 0,
+            bump: 255,
+            vault_bump: 255,
+        };
+
+        treasury.record_collection(300).unwrap();
+        treasury.record_collection(200).unwrap();
+        treasury.record_withdrawal(450).unwrap();
+        assert_eq!((treasury.total_collected, treasury.total_withdrawn), (500, 450));
+
+        treasury.total_collected = u64::MAX;
+        assert!(treasury.record_collection(1).is_err());
+    }
+}
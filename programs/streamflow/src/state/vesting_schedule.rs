@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+
+/// Maximum number of checkpoints a single schedule can hold
+pub const MAX_CHECKPOINTS: usize = 32;
+
+/// A point on a piecewise vesting curve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// When this checkpoint is reached (Unix timestamp)
+    pub timestamp: i64,
+    /// Total amount vested once the checkpoint is reached
+    pub cumulative_amount: u64,
+}
+
+impl Checkpoint {
+    pub const LEN: usize = 8 + // timestamp
+        8; // cumulative_amount
+}
+
+/// Irregular vesting curve for a `StreamType::Schedule` stream
+#[account]
+pub struct VestingSchedule {
+    /// The stream this schedule belongs to
+    pub stream: Pubkey,
+    /// Interpolate linearly between checkpoints instead of stepping at each one
+    pub interpolate: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// Checkpoints ordered by timestamp, the last one vests the full amount
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl VestingSchedule {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // stream
+        1 + // interpolate
+        1 + // bump
+        4 + MAX_CHECKPOINTS * Checkpoint::LEN; // checkpoints
+
+    /// Validate checkpoints against the stream they will govern
+    pub fn validate_checkpoints(
+        checkpoints: &[Checkpoint],
+        start_time: i64,
+        end_time: i64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            !checkpoints.is_empty() && checkpoints.len() <= MAX_CHECKPOINTS,
+            StreamFlowError::InvalidMilestoneParameters
+        );
+
+        let mut previous_timestamp = start_time;
+        let mut previous_amount = 0;
+        for (index, checkpoint) in checkpoints.iter().enumerate() {
+            let ordered = if index == 0 {
+                checkpoint.timestamp >= previous_timestamp
+            } else {
+                checkpoint.timestamp > previous_timestamp
+            };
+            require!(
+                ordered && checkpoint.cumulative_amount >= previous_amount,
+                StreamFlowError::InvalidVestingSchedule
+            );
+            previous_timestamp = checkpoint.timestamp;
+            previous_amount = checkpoint.cumulative_amount;
+        }
+
+        require!(
+            previous_timestamp == end_time && previous_amount == amount,
+            StreamFlowError::InvalidVestingSchedule
+        );
+
+        Ok(())
+    }
+
+    /// Amount vested at `current_time` according to the checkpoints
+    pub fn vested_amount(&self, current_time: i64) -> Result<u64> {
+        let next_index = self
+            .checkpoints
+            .iter()
+            .position(|checkpoint| checkpoint.timestamp > current_time);
+
+        let next_index = match next_index {
+            Some(index) => index,
+            None => {
+                return Ok(self
+                    .checkpoints
+                    .last()
+                    .map_or(0, |checkpoint| checkpoint.cumulative_amount))
+            },
+        };
+
+        if next_index == 0 {
+            return Ok(0);
+        }
+
+        let previous = self.checkpoints[next_index - 1];
+        if !self.interpolate {
+            return Ok(previous.cumulative_amount);
+        }
+
+        let next = self.checkpoints[next_index];
+        let elapsed = (current_time - previous.timestamp) as u128;
+        let span = (next.timestamp - previous.timestamp) as u128;
+        let interpolated = ((next.cumulative_amount - previous.cumulative_amount) as u128)
+            .checked_mul(elapsed)
+            .and_then(|result| result.checked_div(span))
+            .and_then(|result| u64::try_from(result).ok())
+            .ok_or(StreamFlowError::MathematicalOverflow)?;
+
+        Ok(previous.cumulative_amount + interpolated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(interpolate: bool) -> VestingSchedule {
+        VestingSchedule {
+            stream: Pubkey::new_unique(),
+            interpolate,
+            bump: 255,
+            checkpoints: vec![
+                Checkpoint { timestamp: 1_000, cumulative_amount: 100 },
+                Checkpoint { timestamp: 2_000, cumulative_amount: 600 },
+                Checkpoint { timestamp: 3_000, cumulative_amount: 1_000 },
+            ],
+        }
+    }
+
+    #[test]
+    fn stepped_schedule_holds_each_checkpoint() {
+        let schedule = schedule(false);
+
+        assert_eq!(schedule.vested_amount(999).unwrap(), 0);
+        assert_eq!(schedule.vested_amount(1_000).unwrap(), 100);
+        assert_eq!(schedule.vested_amount(1_999).unwrap(), 100);
+        assert_eq!(schedule.vested_amount(2_000).unwrap(), 600);
+        assert_eq!(schedule.vested_amount(5_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn interpolated_schedule_is_linear_between_checkpoints() {
+        let schedule = schedule(true);
+
+        assert_eq!(schedule.vested_amount(999).unwrap(), 0);
+        assert_eq!(schedule.vested_amount(1_500).unwrap(), 350);
+        assert_eq!(schedule.vested_amount(2_250).unwrap(), 700);
+        assert_eq!(schedule.vested_amount(3_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn checkpoints_must_cover_the_stream() {
+        let checkpoints = schedule(false).checkpoints;
+
+        assert!(VestingSchedule::validate_checkpoints(&checkpoints, 500, 3_000, 1_000).is_ok());
+        // The last checkpoint must land on the end and vest everything
+        assert!(VestingSchedule::validate_checkpoints(&checkpoints, 500, 3_500, 1_000).is_err());
+        assert!(VestingSchedule::validate_checkpoints(&checkpoints, 500, 3_000, 1_200).is_err());
+        // No checkpoint before the start
+        assert!(VestingSchedule::validate_checkpoints(&checkpoints, 1_500, 3_000, 1_000).is_err());
+        assert!(VestingSchedule::validate_checkpoints(&[], 500, 3_000, 1_000).is_err());
+
+        let mut unordered = checkpoints.clone();
+        unordered.swap(0, 1);
+        assert!(VestingSchedule::validate_checkpoints(&unordered, 500, 3_000, 1_000).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::StreamFlowError;
+
+/// Who may withdraw from a stream besides its holder, and where its payouts go.
+///
+/// The payout destination applies to `withdraw`, `crank_withdraw` and the
+/// recipient's share of `cancel_stream` alike.
+#[account]
+pub struct WithdrawSettings {
+    /// The stream these settings belong to
+    pub stream: Pubkey,
+    /// The holder that registered the settings, they lapse once the stream changes hands
+    pub recipient: Pubkey,
+    /// Paid the rent of this account and gets it back when the stream is closed
+    pub payer: Pubkey,
+    /// Account allowed to trigger withdrawals on the holder's behalf
+    pub delegate: Option<Pubkey>,
+    /// Token account every payout goes to, even when owned by someone else
+    pub payout_destination: Option<Pubkey>,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl WithdrawSettings {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // stream
+        32 + // recipient
+        32 + // payer
+        1 + 32 + // delegate
+        1 + 32 + // payout_destination
+        1; // bump
+
+    /// Take the settings over for `recipient`, dropping anything a previous holder registered
+    pub fn claim(&mut self, stream: Pubkey, recipient: Pubkey, payer: Pubkey, bump: u8) {
+        if self.stream == Pubkey::default() {
+            self.payer = payer;
+        }
+        if self.recipient != recipient {
+            self.delegate = None;
+            self.payout_destination = None;
+        }
+        self.stream = stream;
+        self.recipient = recipient;
+        self.bump = bump;
+    }
+
+    /// The settings stored at `account` if they were registered by `recipient`.
+    ///
+    /// `account` is the stream's settings PDA, which does not exist until a setter has run.
+    pub fn load(account: &AccountInfo, recipient: &Pubkey) -> Result<Option<Self>> {
+        if *account.owner != crate::ID {
+            return Ok(None);
+        }
+
+        let settings = Self::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        Ok(Some(settings).filter(|settings| settings.recipient == *recipient))
+    }
+
+    /// Check that `token_account` may receive the payouts of `recipient`
+    pub fn check_payout_account(
+        settings: Option<&Self>,
+        token_account: &InterfaceAccount<TokenAccount>,
+        recipient: &Pubkey,
+    ) -> Result<()> {
+        match settings.and_then(|settings| settings.payout_destination) {
+            Some(payout_destination) => require_keys_eq!(
+                token_account.key(),
+                payout_destination,
+                StreamFlowError::InvalidTokenAccount
+            ),
+            None => require_keys_eq!(token_account.owner, *recipient, StreamFlowError::InvalidTokenAccount),
+        }
+        Ok(())
+    }
+}
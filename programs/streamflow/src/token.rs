@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, InitializeAccount3, Mint, SyncNative, TokenInterface,
+};
+
+use crate::error::StreamFlowError;
+
+/// Move `amount` tokens with `transfer_checked` under either token program.
+///
+/// Transfer-hook mints need the hook's extra accounts, which callers pass
+/// through `remaining_accounts`; they are ignored for mints without a hook.
+pub fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        to,
+        authority,
+        remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Tokens a Token-2022 transfer-fee extension withholds when `amount` is sent
+pub fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(StreamFlowError::FeeCalculationError.into()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// What arrives at the destination when `amount` is sent
+pub fn amount_after_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    amount
+        .checked_sub(transfer_fee(mint, amount)?)
+        .ok_or(StreamFlowError::FeeCalculationError.into())
+}
+
+/// Move transfer fees withheld in `account` to its mint, Token-2022 refuses to close an account holding them
+pub fn harvest_withheld_fees<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    account: AccountInfo<'info>,
+) -> Result<()> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let withheld_amount = {
+        let account_data = account.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        account_state
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |transfer_fee_amount| u64::from(transfer_fee_amount.withheld_amount))
+    };
+    if withheld_amount == 0 {
+        return Ok(());
+    }
+
+    let instruction = harvest_withheld_tokens_to_mint(token_program.key, &mint.key(), &[&account.key()])?;
+    invoke(&instruction, &[mint.to_account_info(), account]).map_err(Into::into)
+}
+
+/// Space a token account for `mint` needs, including the extensions the mint requires
+pub fn token_account_len(mint: &InterfaceAccount<Mint>) -> Result<usize> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(spl_token::state::Account::LEN);
+    }
+
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let account_extensions =
+        ExtensionType::get_required_init_account_extensions(&mint_state.get_extension_types()?);
+    ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&account_extensions)
+        .map_err(Into::into)
+}
+
+/// Whether `mint` is the wrapped SOL mint of either token program
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+/// Wrap `lamports` from `payer` into the wrapped SOL token account `to`
+pub fn wrap_sol<'info>(
+    system_program: &Program<'info, System>,
+    token_program: &Interface<'info, TokenInterface>,
+    payer: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer { from: payer, to: to.clone() },
+        ),
+        lamports,
+    )?;
+
+    token_interface::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative { account: to },
+    ))
+}
+
+/// Deliver wrapped SOL held by `from` as native SOL.
+///
+/// The total of `deliveries` is moved into a temporary token account at the
+/// `unwrap_account` PDA, which is closed straight away. `payer` funds its rent
+/// and receives all of its lamports on close, then forwards each delivery to
+/// its destination, so `payer` ends up exactly where it started.
+pub fn unwrap_sol<'info>(
+    system_program: &Program<'info, System>,
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: AccountInfo<'info>,
+    authority_seeds: &[&[&[u8]]],
+    unwrap_account: AccountInfo<'info>,
+    unwrap_seeds: &[&[u8]],
+    payer: AccountInfo<'info>,
+    deliveries: &[(AccountInfo<'info>, u64)],
+) -> Result<()> {
+    require!(is_native_mint(&mint.key()), StreamFlowError::InvalidTokenMint);
+
+    let total_amount = deliveries
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+        .ok_or(StreamFlowError::MathematicalOverflow)?;
+    if total_amount == 0 {
+        return Ok(());
+    }
+
+    // The PDA is predictable, so tolerate lamports someone already sent to it
+    crate::create_pda_account(
+        system_program,
+        payer.clone(),
+        unwrap_account.clone(),
+        spl_token::state::Account::LEN,
+        token_program.key,
+        unwrap_seeds,
+    )?;
+
+    token_interface::initialize_account3(CpiContext::new(
+        token_program.to_account_info(),
+        InitializeAccount3 {
+            account: unwrap_account.clone(),
+            mint: mint.to_account_info(),
+            authority: authority.clone(),
+        },
+    ))?;
+
+    transfer_tokens(
+        token_program,
+        from,
+        mint,
+        unwrap_account.clone(),
+        authority.clone(),
+        &[],
+        total_amount,
+        authority_seeds,
+    )?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: unwrap_account,
+            destination: payer.clone(),
+            authority,
+        },
+        authority_seeds,
+    ))?;
+
+    for (destination, amount) in deliveries {
+        if *amount == 0 || destination.key() == payer.key() {
+            continue;
+        }
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer { from: payer.clone(), to: destination.clone() },
+            ),
+            *amount,
+        )?;
+    }
+
+    Ok(())
+}